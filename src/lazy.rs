@@ -0,0 +1,164 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+    fmt::Display,
+    ops::{AddAssign, SubAssign},
+};
+
+use crate::{Poly, PolyTerm};
+
+/// A `Poly` that simplifies itself lazily, on the first read after a mutation.
+///
+/// `Poly` itself never simplifies behind your back: `x + x` stays two terms
+/// (and prints as `x +x`) until `simplify` is called, and a long chain of
+/// `+=` keeps growing the unmerged term vector. This wrapper tracks a dirty
+/// flag instead. Mutations (which need `&mut self`) append terms as cheaply
+/// as on `Poly` and mark it dirty. Reads (`get`, `Display`, `degree`,
+/// `evaluate`, ..) take `&self`, simplify at most once and cache the
+/// canonical form until the next mutation.
+///
+/// Reads mutate through a `RefCell`, which can't conflict: every mutation
+/// takes `&mut self`, so no `Ref` handed out by `get` is alive by then. This
+/// also makes the wrapper `!Sync`, so share the `Poly` from `into_inner` across threads.
+#[derive(Debug, Clone)]
+pub struct SimplifyOnRead {
+    poly: RefCell<Poly>,
+    dirty: Cell<bool>,
+}
+
+impl SimplifyOnRead {
+    pub fn new(poly: Poly) -> Self {
+        SimplifyOnRead {
+            poly: RefCell::new(poly),
+            dirty: Cell::new(true),
+        }
+    }
+
+    /// Whether the next read has to simplify first.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.get()
+    }
+
+    /// The canonical form, simplifying first if anything changed since the last read.
+    pub fn get(&self) -> Ref<'_, Poly> {
+        if self.dirty.get() {
+            self.poly.borrow_mut().simplify();
+            self.dirty.set(false);
+        }
+        self.poly.borrow()
+    }
+
+    /// Direct access to the stored terms for any other mutation. Marks the
+    /// polynomial dirty, whatever is done with it.
+    pub fn get_mut(&mut self) -> &mut Poly {
+        self.dirty.set(true);
+        self.poly.get_mut()
+    }
+
+    pub fn push_term(&mut self, term: PolyTerm) -> &mut Self {
+        self.get_mut().push_term(term);
+        self
+    }
+
+    /// The canonical form, by value.
+    pub fn into_inner(self) -> Poly {
+        let mut poly = self.poly.into_inner();
+        if self.dirty.get() {
+            poly.simplify();
+        }
+        poly
+    }
+
+    pub fn degree(&self) -> usize {
+        self.get().degree()
+    }
+
+    pub fn evaluate(&self, bindings: &HashMap<String, f64>) -> Option<f64> {
+        self.get().evaluate(bindings)
+    }
+}
+
+impl Default for SimplifyOnRead {
+    fn default() -> Self {
+        SimplifyOnRead::new(Poly::zero())
+    }
+}
+
+impl From<Poly> for SimplifyOnRead {
+    fn from(poly: Poly) -> Self {
+        SimplifyOnRead::new(poly)
+    }
+}
+
+impl PartialEq for SimplifyOnRead {
+    fn eq(&self, other: &Self) -> bool {
+        *self.get() == *other.get()
+    }
+}
+
+impl AddAssign<&Poly> for SimplifyOnRead {
+    fn add_assign(&mut self, rhs: &Poly) {
+        *self.get_mut() += rhs;
+    }
+}
+
+impl SubAssign<&Poly> for SimplifyOnRead {
+    fn sub_assign(&mut self, rhs: &Poly) {
+        *self.get_mut() -= rhs;
+    }
+}
+
+impl Display for SimplifyOnRead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PolyVar;
+
+    fn x() -> Poly {
+        Poly(vec![PolyTerm {
+            coeff: 1.,
+            vars: vec![PolyVar {
+                sym: "x".into(),
+                deg: 1,
+            }],
+        }])
+    }
+
+    #[test]
+    fn simplifies_on_read() {
+        let mut p = SimplifyOnRead::from(x());
+        p += &x();
+        assert!(p.is_dirty());
+        assert_eq!(p.to_string(), "2x");
+        assert!(!p.is_dirty());
+        assert_eq!(p.get().0.len(), 1);
+
+        p -= &x();
+        p -= &x();
+        assert!(p.is_dirty());
+        assert_eq!(p.degree(), 0);
+        assert_eq!(p.into_inner(), Poly::zero());
+    }
+
+    #[test]
+    fn many_adds() {
+        let mut p = SimplifyOnRead::default();
+        for _ in 0..1000 {
+            p += &x();
+        }
+        assert_eq!(
+            p.evaluate(&HashMap::from([("x".to_string(), 2.)])),
+            Some(2000.)
+        );
+        assert_eq!(p.get().0.len(), 1);
+
+        p.get_mut().0[0].coeff = 1.;
+        assert!(p.is_dirty());
+        assert_eq!(p, SimplifyOnRead::new(x()));
+    }
+}
@@ -1,9 +1,35 @@
+mod lazy;
+mod parse;
+mod rational;
+mod tolerance;
+
+pub use lazy::SimplifyOnRead;
+pub use rational::RatCoeff;
+pub use tolerance::Tolerant;
+
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
     fmt::Display,
-    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign},
 };
 
+/// A sum of terms.
+///
+/// The terms are public so a `Poly` can be built by hand, but most methods
+/// expect the canonical form produced by `simplify`:
+/// - no terms with a zero coeff
+/// - no vars with a zero degree, and each symbol appears at most once per term
+/// - vars within a term sorted by symbol
+/// - like terms merged
+/// - terms sorted by degree desc. (ties broken lexicographically)
+///
+/// `PartialEq`, `Display` and `degree` work on the terms as they are stored,
+/// so two equal polynomials only compare equal (and print the same) once both
+/// are simplified. Use `Poly::new` or `canonical` to get there, or wrap the
+/// polynomial in a `SimplifyOnRead` to have that happen lazily. Comparisons
+/// are exact, a `Tolerant` carries a tolerance for them instead.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Poly(pub Vec<PolyTerm>);
 
@@ -13,41 +39,246 @@ pub struct PolyTerm {
     pub vars: Vec<PolyVar>,
 }
 
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord)]
 pub struct PolyVar {
     pub sym: String,
     pub deg: usize,
 }
 
-impl Poly {
-    pub fn simplify(&mut self) {
-        // remove terms with zero coeff
-        // x2 + 0y2 + 3xy => x2 + 3xy
-        self.0.retain(|term| term.coeff != 0.);
+/// A total order on monomials. Symbols are ranked alphabetically, an earlier
+/// symbol being the bigger variable (x > y > z).
+/// `simplify` sorts terms by `GrLex`, biggest first.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MonomialOrder {
+    /// compare exponents symbol by symbol
+    /// x > y2 > y > 1
+    Lex,
+    /// compare total degrees, then `Lex`
+    /// y2 > x > y > 1
+    GrLex,
+    /// compare total degrees, then the term with the smaller exponent of the
+    /// last symbol where they differ is bigger
+    /// x2z > xy2 under GrLex, but xy2 > x2z under GRevLex
+    GRevLex,
+}
 
-        // remove vars with zero degree
-        // 4x0y2 => 4y2
-        for term in &mut self.0 {
-            term.vars.retain(|var| var.deg != 0);
+impl MonomialOrder {
+    /// Compares the monomials (coeffs are ignored) of two terms.
+    /// `Greater` means `term1` comes first in this order.
+    pub fn compare(&self, term1: &PolyTerm, term2: &PolyTerm) -> Ordering {
+        let mut term1 = term1.clone();
+        let mut term2 = term2.clone();
+        term1.normalize_vars();
+        term2.normalize_vars();
+
+        // (exponent in term1, exponent in term2) for every symbol, alphabetically
+        let mut exps = BTreeMap::<&str, (usize, usize)>::new();
+        for var in &term1.vars {
+            exps.entry(&var.sym).or_default().0 = var.deg;
         }
+        for var in &term2.vars {
+            exps.entry(&var.sym).or_default().1 = var.deg;
+        }
+        let mut diffs = exps.values().filter(|(deg1, deg2)| deg1 != deg2);
 
-        // add together degrees of vars with same symbol
-        // 4x2x3y4 => 4x5y4
-        let mut m = HashMap::<String, usize>::new();
-        for term in &mut self.0 {
-            while let Some(var) = term.vars.pop() {
-                let entry = m.entry(var.sym).or_insert(0);
-                *entry += var.deg;
+        let lex = |diffs: &mut dyn Iterator<Item = &(usize, usize)>| {
+            diffs
+                .next()
+                .map(|(deg1, deg2)| deg1.cmp(deg2))
+                .unwrap_or(Ordering::Equal)
+        };
+        let degree = term1.degree().cmp(&term2.degree());
+
+        match self {
+            MonomialOrder::Lex => lex(&mut diffs),
+            MonomialOrder::GrLex => degree.then_with(|| lex(&mut diffs)),
+            MonomialOrder::GRevLex => degree.then_with(|| {
+                diffs
+                    .next_back()
+                    .map(|(deg1, deg2)| deg2.cmp(deg1))
+                    .unwrap_or(Ordering::Equal)
+            }),
+        }
+    }
+}
+
+/// Symmetry of a polynomial under `sym -> -sym`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Parity {
+    /// only even powers, p(-x) = p(x)
+    /// x4 + x2 + 1
+    Even,
+    /// only odd powers, p(-x) = -p(x)
+    /// x3 + x
+    Odd,
+    /// x2 + x
+    Neither,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SymbaError {
+    /// the polynomial at this index is not linear in the given symbols
+    /// (or has a term with a symbol outside of them)
+    NotLinear { index: usize },
+    /// the polynomial has symbols other than `sym`
+    NotUnivariate { sym: String },
+    /// the matrix is not square
+    NotSquare,
+    /// a non constant polynomial raised to a negative power is not a polynomial
+    NegativeExponent,
+    /// a division (or a fraction) by zero
+    DivisionByZero,
+    /// the polynomial is constant (or zero) where a root is needed
+    ConstantPolynomial,
+    /// an operation would produce more terms than allowed
+    TooManyTerms { terms: usize, max: usize },
+    /// `divisor * quotient + remainder` is off from the dividend by `residual`
+    InexactDivision { residual: Poly },
+    /// the input could not be parsed as a polynomial, `pos` is a byte offset
+    Parse { pos: usize, reason: String },
+}
+
+impl Display for SymbaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbaError::NotLinear { index } => {
+                write!(f, "polynomial at index {} is not linear", index)
+            }
+            SymbaError::NotUnivariate { sym } => {
+                write!(f, "polynomial is not univariate in {}", sym)
+            }
+            SymbaError::NotSquare => write!(f, "matrix is not square"),
+            SymbaError::NegativeExponent => {
+                write!(f, "non constant polynomial raised to a negative power")
+            }
+            SymbaError::DivisionByZero => write!(f, "division by zero"),
+            SymbaError::ConstantPolynomial => write!(f, "polynomial is constant"),
+            SymbaError::TooManyTerms { terms, max } => {
+                write!(f, "{} terms exceeds the maximum of {}", terms, max)
             }
-            for (sym, deg) in m.drain() {
-                term.vars.push(PolyVar { sym, deg })
+            SymbaError::InexactDivision { residual } => {
+                write!(f, "division is off by a residual of {}", residual)
+            }
+            SymbaError::Parse { pos, reason } => {
+                write!(f, "parse error at {}: {}", pos, reason)
             }
         }
+    }
+}
+
+impl std::error::Error for SymbaError {}
+
+impl Poly {
+    /// The zero polynomial, which has no terms.
+    pub fn zero() -> Self {
+        Poly(vec![])
+    }
+
+    /// The constant polynomial `1`.
+    pub fn one() -> Self {
+        Poly(vec![PolyTerm {
+            coeff: 1.,
+            vars: vec![],
+        }])
+    }
+
+    /// Builds a polynomial that is already in canonical form.
+    pub fn new(terms: Vec<PolyTerm>) -> Self {
+        let mut p = Poly(terms);
+        p.simplify();
+        p
+    }
+
+    /// Whether the polynomial is already in the canonical form `simplify`
+    /// produces (see `Poly`), checked without cloning or modifying anything.
+    pub fn is_canonical(&self) -> bool {
+        let terms_ok = self.0.iter().all(|term| {
+            term.coeff != 0.
+                && term.vars.iter().all(|var| var.deg != 0)
+                && term.vars.windows(2).all(|w| w[0].sym < w[1].sym)
+        });
+
+        // strictly ordered terms also means no two like terms
+        let order_ok = self.0.windows(2).all(|w| {
+            w[1].degree()
+                .cmp(&w[0].degree())
+                .then_with(|| lex_cmp(&w[0].vars, &w[1].vars))
+                == Ordering::Less
+        });
+
+        terms_ok && order_ok
+    }
+
+    /// Appends a term without simplifying, so many terms can be pushed
+    /// cheaply and merged by a single `simplify` (or `canonical`) at the end.
+    pub fn push_term(&mut self, term: PolyTerm) -> &mut Self {
+        self.0.push(term);
+        self
+    }
+
+    /// The number of stored terms. Only the number of distinct monomials
+    /// once simplified.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no stored terms. A simplified polynomial is empty
+    /// exactly when it is zero.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The stored term at `index`, `None` if out of bounds. Positions follow
+    /// the canonical order once simplified, `p.get(0)` being the leading term.
+    pub fn get(&self, index: usize) -> Option<&PolyTerm> {
+        self.0.get(index)
+    }
+
+    /// Removes every term, leaving the zero polynomial.
+    pub fn clear(&mut self) -> &mut Self {
+        self.0.clear();
+        self
+    }
+
+    /// Consumes the polynomial and hands back its simplified terms without
+    /// cloning them. `Poly::from_iter` (or `collect`) goes the other way.
+    pub fn into_terms(mut self) -> Vec<PolyTerm> {
+        self.simplify();
+        self.0
+    }
+
+    /// The stored terms, biggest first in `order`, without touching the
+    /// stored vector. For a simplified polynomial the sequence only depends on
+    /// the polynomial's value, not on how it was built.
+    pub fn terms_ordered(&self, order: MonomialOrder) -> Vec<&PolyTerm> {
+        let mut terms = self.0.iter().collect::<Vec<_>>();
+        terms.sort_by(|term1, term2| order.compare(term2, term1));
+        terms
+    }
+
+    /// Returns a simplified clone, leaving `self` untouched.
+    pub fn canonical(&self) -> Self {
+        Poly::new(self.0.clone())
+    }
 
-        // order vars
+    /// Wraps the polynomial with a tolerance that its `simplify` and
+    /// comparisons use, see `Tolerant`.
+    pub fn with_tolerance(self, eps: f64) -> Tolerant {
+        Tolerant::new(self, eps)
+    }
+
+    pub fn simplify(&mut self) -> &mut Self {
+        // remove terms with zero coeff
+        // x2 + 0y2 + 3xy => x2 + 3xy
+        self.0.retain(|term| term.coeff != 0.);
+
+        // add together degrees of vars with same symbol, drop zero degree vars
+        // and order vars
+        // 4x2x3y4 => 4x5y4
+        // 4x0y2 => 4y2
         // 4x2y + 10yx2 => 4x2y + 10x2y
         for term in &mut self.0 {
-            term.vars.sort_by(|var1, var2| var1.sym.cmp(&var2.sym));
+            term.normalize_vars();
         }
 
         // add together coeffs of like terms
@@ -61,24 +292,320 @@ impl Poly {
             self.0.push(PolyTerm { coeff, vars })
         }
 
-        // sort according to degree desc.
+        // remove terms that cancelled out while merging.
+        // this also catches -0 (-0 == 0), so no -0 coeff survives
+        // 4x2y - 4x2y + 1 => 1
+        self.0.retain(|term| term.coeff != 0.);
+
+        // sort according to degree desc. and lexicographically among equal degrees
         // 3a2 + 1 + a3 + a => a3 + 3a2 + a + 1
+        // b2 + ab + a2 => a2 + ab + b2
+        self.0.sort_by(|term1, term2| {
+            term2
+                .degree()
+                .cmp(&term1.degree())
+                .then_with(|| lex_cmp(&term1.vars, &term2.vars))
+        });
+
+        self
+    }
+
+    /// Simplifies, then also drops the terms whose merged coeff is within
+    /// `eps` of zero, like the leftover of `0.1x + 0.2x - 0.3x`. An `eps` of
+    /// zero is exactly `simplify`. NaN coeffs are kept, as in `simplify`.
+    ///
+    /// x2 + 1e-12x + 1 with eps 1e-9 => x2 + 1
+    pub fn simplify_with_tolerance(&mut self, eps: f64) -> &mut Self {
+        self.simplify();
         self.0
-            .sort_by_key(|term| term.vars.iter().map(|var| var.deg).sum::<usize>());
-        self.0.reverse();
+            .retain(|term| term.coeff.abs() > eps || term.coeff.is_nan());
+        self
+    }
+
+    /// `simplify`, with `var_order` as the symbol precedence for ordering the
+    /// vars within each term and the terms, unlisted symbols following
+    /// alphabetically. The same as `set_var_order`, under the name that reads
+    /// as the ordered counterpart of `simplify`.
+    ///
+    /// 2xyz + x2 with order [z, y, x] => 2zyx + x2
+    pub fn simplify_ordered(&mut self, var_order: &[&str]) -> &mut Self {
+        self.set_var_order(var_order)
+    }
+
+    /// Simplifies, then orders the vars within each term and the terms
+    /// themselves using `order` as the symbol precedence. Symbols not in
+    /// `order` come after the listed ones, alphabetically.
+    ///
+    /// 3xt + t2 + y with order [t] => t2 + 3tx + y
+    ///
+    /// A later `simplify` restores the default alphabetical order.
+    /// `simplify_ordered` is the same operation under another name.
+    pub fn set_var_order(&mut self, order: &[&str]) -> &mut Self {
+        self.simplify();
+
+        let sym_cmp = |sym1: &str, sym2: &str| {
+            let rank = |sym: &str| order.iter().position(|s| *s == sym).unwrap_or(order.len());
+            rank(sym1).cmp(&rank(sym2)).then_with(|| sym1.cmp(sym2))
+        };
+
+        for term in &mut self.0 {
+            term.vars
+                .sort_by(|var1, var2| sym_cmp(&var1.sym, &var2.sym));
+        }
+        self.0.sort_by(|term1, term2| {
+            term2
+                .degree()
+                .cmp(&term1.degree())
+                .then_with(|| lex_cmp_by(&term1.vars, &term2.vars, sym_cmp))
+        });
+
+        self
+    }
+
+    /// Substitutes `val` for `sym` and simplifies, so the result is canonical
+    /// and no zero degree vars are left behind.
+    ///
+    /// x2 + y with x -> 2 => y + 4
+    pub fn substitute(&mut self, sym: &str, val: f64) -> &mut Self {
+        for term in &mut self.0 {
+            for var in &mut term.vars {
+                if var.sym == sym {
+                    term.coeff *= pow_exact(val, var.deg);
+                    var.deg = 0;
+                }
+            }
+        }
+
+        self.simplify()
+    }
+
+    /// Substitutes the polynomial `val` for `sym`, expanding and simplifying.
+    /// `val` may itself contain `sym`.
+    ///
+    /// x2 + y with x -> x + 1 => x2 + 2x + y + 1
+    pub fn substitute_poly(&mut self, sym: &str, val: &Poly) -> &mut Self {
+        let mut powers = HashMap::<usize, Poly>::new();
+
+        let mut res = Poly(vec![]);
+        for mut term in self.0.drain(..) {
+            // an unnormalized term may hold `sym` more than once
+            let deg = term
+                .vars
+                .iter()
+                .filter(|var| var.sym == sym)
+                .map(|var| var.deg)
+                .sum::<usize>();
+            if deg == 0 {
+                res.0.push(term);
+                continue;
+            }
+            term.vars.retain(|var| var.sym != sym);
+            let power = powers.entry(deg).or_insert_with(|| val.pow(deg));
+            res += &*power * &term;
+        }
+
+        *self = res;
+        self.simplify()
+    }
+
+    /// Substitutes every bound symbol by its polynomial simultaneously: the
+    /// replacements are not themselves substituted into, so unlike a sequence
+    /// of `substitute_poly` calls, swapping `x -> y, y -> x` in `x2y` gives `xy2`.
+    ///
+    /// x2y with x -> y, y -> x => xy2
+    pub fn substitute_polys(&self, bindings: &HashMap<String, Poly>) -> Poly {
+        let mut powers = HashMap::<(&str, usize), Poly>::new();
+
+        let mut res = Poly(vec![]);
+        for term in &self.0 {
+            let mut product = Poly(vec![PolyTerm {
+                coeff: term.coeff,
+                vars: vec![],
+            }]);
+            for var in &term.vars {
+                match bindings.get(&var.sym) {
+                    Some(val) => {
+                        let power = powers
+                            .entry((&var.sym, var.deg))
+                            .or_insert_with(|| val.pow(var.deg));
+                        product = &product * &*power;
+                    }
+                    None => {
+                        product = &product
+                            * &PolyTerm {
+                                coeff: 1.,
+                                vars: vec![var.clone()],
+                            }
+                    }
+                }
+            }
+            res += product;
+        }
+
+        res.simplify();
+        res
+    }
+
+    /// The polynomial valued counterpart of `evaluate`: the polynomial with
+    /// every bound symbol replaced by its polynomial (all at once, see
+    /// `substitute_polys`) and the others left symbolic. Binding constant
+    /// polynomials evaluates, binding other polynomials composes.
+    ///
+    /// x2 + y with x -> t + 1 => t2 + 2t + y + 1
+    pub fn call(&self, bindings: &HashMap<String, Poly>) -> Poly {
+        self.substitute_polys(bindings)
+    }
+
+    /// The coeff of the first term in canonical order, i.e. of the highest
+    /// degree term. Zero for the zero polynomial.
+    pub fn leading_coeff(&self) -> f64 {
+        self.canonical()
+            .0
+            .first()
+            .map(|term| term.coeff)
+            .unwrap_or_default()
+    }
+
+    /// Whether every coeff (after simplifying) is within `eps` of an integer.
+    pub fn is_integral(&self, eps: f64) -> bool {
+        self.canonical()
+            .0
+            .iter()
+            .all(|term| (term.coeff - term.coeff.round()).abs() <= eps)
+    }
+
+    /// The simplified polynomial with every coeff rounded to the nearest
+    /// integer, or `None` if some coeff is further than `eps` from one.
+    ///
+    /// 2.0000001x + 3 with eps 1e-6 => 2x + 3
+    pub fn to_integral(&self, eps: f64) -> Option<Poly> {
+        if !self.is_integral(eps) {
+            return None;
+        }
+
+        let mut p = self.canonical();
+        for term in &mut p.0 {
+            term.coeff = term.coeff.round();
+        }
+        Some(p)
+    }
+
+    /// The largest absolute coeff (after simplifying), zero for the zero polynomial.
+    pub fn max_norm(&self) -> f64 {
+        self.canonical()
+            .0
+            .iter()
+            .map(|term| term.coeff.abs())
+            .fold(0., f64::max)
+    }
+
+    /// The sum of the absolute coeffs (after simplifying).
+    pub fn l1_norm(&self) -> f64 {
+        self.canonical().0.iter().map(|term| term.coeff.abs()).sum()
+    }
+
+    /// The euclidean norm of the coeffs (after simplifying).
+    pub fn l2_norm(&self) -> f64 {
+        self.canonical()
+            .0
+            .iter()
+            .map(|term| term.coeff * term.coeff)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Substitutes every binding in a single pass over the terms, then simplifies once.
+    pub fn substitute_all(&mut self, bindings: &HashMap<String, f64>) -> &mut Self {
+        self.substitute_fn(|sym| bindings.get(sym).copied())
+    }
+
+    /// Like `substitute_all`, but the value of each symbol comes from `f`,
+    /// symbols it returns `None` for are left alone. `f` is queried once per
+    /// occurrence of a var.
+    ///
+    /// 2c1x + c2 + x with every c* set to 0 => x
+    pub fn substitute_fn(&mut self, f: impl Fn(&str) -> Option<f64>) -> &mut Self {
+        for term in &mut self.0 {
+            for var in &mut term.vars {
+                if let Some(val) = f(&var.sym) {
+                    term.coeff *= pow_exact(val, var.deg);
+                    var.deg = 0;
+                }
+            }
+        }
+
+        self.simplify()
+    }
+
+    /// The value of the polynomial with every symbol bound.
+    /// `None` if some symbol has no binding.
+    pub fn evaluate(&self, bindings: &HashMap<String, f64>) -> Option<f64> {
+        let mut p = self.clone();
+        p.substitute_all(bindings);
+
+        match p.0.as_slice() {
+            [] => Some(0.),
+            [term] if term.vars.is_empty() => Some(term.coeff),
+            _ => None,
+        }
+    }
+
+    /// The value of the polynomial with every symbol bound, by nested horner:
+    /// collect by one symbol, evaluate its coeffs recursively in the remaining
+    /// symbols, then horner in that symbol. Far fewer multiplications than
+    /// `evaluate` and no intermediate polynomials. Unbound symbols give NaN.
+    ///
+    /// x2y + 3xy + x => (yx + (3y + 1))x, at x=2 y=3 => 32
+    pub fn eval_multivariate_horner(&self, bindings: &HashMap<String, f64>) -> f64 {
+        let mut p = self.clone();
+        p.simplify();
+        multivariate_horner(p.0, bindings)
     }
 
-    pub fn substitute(&mut self, sym: &str, val: f64) {
+    /// Substitutes the rational `num / den` for `sym`. Each coeff is scaled by
+    /// `num^deg / den^deg`, with both powers (and, for an integer coeff, its
+    /// product with `num^deg`) computed in integers when they fit, so `x2` at
+    /// `1/3` is `1. / 9.` rather than `(1. / 3.).powi(2)`. The division then
+    /// rounds once, and the result is correctly rounded as long as the
+    /// numerator and `den^deg` are below 2^53; otherwise their conversions to
+    /// `f64` round too. Simplifies afterwards, so the result is canonical and
+    /// no zero degree vars are left behind.
+    ///
+    /// Panics if `den` is zero.
+    pub fn substitute_ratio(&mut self, sym: &str, num: i64, den: i64) -> &mut Self {
+        assert!(den != 0, "substitute_ratio with a zero denominator");
+
         for term in &mut self.0 {
             for var in &mut term.vars {
                 if var.sym == sym {
-                    term.coeff *= val.powi(var.deg as i32);
+                    let exact = u32::try_from(var.deg).ok().and_then(|deg| {
+                        Some((
+                            (num as i128).checked_pow(deg)?,
+                            (den as i128).checked_pow(deg)?,
+                        ))
+                    });
+                    let integer_coeff = (term.coeff.fract() == 0.
+                        && term.coeff.abs() < i64::MAX as f64)
+                        .then_some(term.coeff as i128);
+                    match exact {
+                        Some((n, d)) => match integer_coeff.and_then(|c| c.checked_mul(n)) {
+                            Some(cn) => term.coeff = cn as f64 / d as f64,
+                            None => term.coeff = term.coeff * n as f64 / d as f64,
+                        },
+                        None => term.coeff *= powu(num as f64 / den as f64, var.deg),
+                    }
                     var.deg = 0;
                 }
             }
         }
+
+        self.simplify()
     }
 
+    /// The highest total degree among the stored terms.
+    ///
+    /// Both a constant and the zero polynomial have degree 0 here, see
+    /// `degree_opt` to tell them apart.
     pub fn degree(&self) -> usize {
         self.0
             .iter()
@@ -88,11 +615,80 @@ impl Poly {
             .unwrap_or_default()
     }
 
-    pub fn differentiate(&mut self, sym: &str) {
+    /// The degree of the simplified polynomial, or `None` for the zero
+    /// polynomial, whose degree is conventionally undefined (or -∞).
+    ///
+    /// 5 => Some(0)
+    /// 0 => None
+    pub fn degree_opt(&self) -> Option<usize> {
+        let p = self.canonical();
+        match p.0.is_empty() {
+            true => None,
+            false => Some(p.degree()),
+        }
+    }
+
+    /// The distinct monomials of the simplified polynomial, each with coeff
+    /// 1, in canonical order. Its shape irrespective of coeffs, e.g. the
+    /// points whose convex hull is the newton polytope.
+    ///
+    /// 3x2 + 0y + 5 => [x2, 1]
+    pub fn support(&self) -> Vec<PolyTerm> {
+        self.canonical()
+            .0
+            .into_iter()
+            .map(|term| PolyTerm {
+                coeff: 1.,
+                vars: term.vars,
+            })
+            .collect()
+    }
+
+    /// The number of simplified terms of each total degree.
+    ///
+    /// x3 + x2y + xy + 4 => {0: 1, 2: 1, 3: 2}
+    pub fn degree_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut m = BTreeMap::new();
+        for term in &self.canonical().0 {
+            *m.entry(term.degree()).or_insert(0) += 1;
+        }
+        m
+    }
+
+    /// Like `degree`, but each exponent is multiplied by the weight of its
+    /// symbol. Symbols missing from `weights` have weight 1.
+    ///
+    /// x2 + y with weight(y) = 2 => 2
+    pub fn weighted_degree(&self, weights: &HashMap<String, usize>) -> usize {
+        self.0
+            .iter()
+            .map(|term| term.weighted_degree(weights))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Whether every (non zero) term has the same `weighted_degree`.
+    pub fn is_weighted_homogeneous(&self, weights: &HashMap<String, usize>) -> bool {
+        let mut p = self.clone();
+        p.simplify();
+
+        let mut degrees = p.0.iter().map(|term| term.weighted_degree(weights));
+        match degrees.next() {
+            Some(first) => degrees.all(|deg| deg == first),
+            None => true,
+        }
+    }
+
+    pub fn differentiate(&mut self, sym: &str) -> &mut Self {
         self.simplify();
 
         for term in &mut self.0 {
-            match term.vars.iter_mut().find(|var| var.sym == sym) {
+            // a zero degree var is a constant, same as a missing one
+            match term
+                .vars
+                .iter_mut()
+                .find(|var| var.sym == sym && var.deg != 0)
+            {
                 Some(var) => {
                     term.coeff *= var.deg as f64;
                     var.deg -= 1;
@@ -101,10 +697,10 @@ impl Poly {
             }
         }
 
-        self.simplify();
+        self.simplify()
     }
 
-    pub fn integrate(&mut self, sym: &str) {
+    pub fn integrate(&mut self, sym: &str) -> &mut Self {
         self.simplify();
 
         for term in &mut self.0 {
@@ -120,166 +716,5702 @@ impl Poly {
             }
         }
 
-        self.simplify();
+        self.simplify()
     }
-}
-
-impl Add for Poly {
-    type Output = Self;
 
-    fn add(mut self, rhs: Self) -> Self::Output {
-        self.0.extend(rhs.0);
-        self
+    /// Every derivative in `sym` of order 0 through `n`, `[p, p', .., p^(n)]`,
+    /// each computed from the previous one. The taylor coeffs at `a` are
+    /// these evaluated at `a` divided by the factorials.
+    ///
+    /// x3 up to 4 => [x3, 3x2, 6x, 6, 0]
+    pub fn derivatives_up_to(&self, sym: &str, n: usize) -> Vec<Poly> {
+        let mut derivatives = vec![self.canonical()];
+        for _ in 0..n {
+            let mut next = derivatives[derivatives.len() - 1].clone();
+            next.differentiate(sym);
+            derivatives.push(next);
+        }
+        derivatives
     }
-}
 
-impl AddAssign for Poly {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0.extend(rhs.0)
+    /// The definite integral of `sym` from `a` to `b`, a polynomial in the
+    /// remaining symbols.
+    ///
+    /// 3x2y wrt x from 0 to 2 => 8y
+    pub fn integrate_definite(&self, sym: &str, a: f64, b: f64) -> Poly {
+        let mut antiderivative = self.clone();
+        antiderivative.integrate(sym);
+
+        let mut upper = antiderivative.clone();
+        upper.substitute(sym, b);
+        let mut lower = antiderivative;
+        lower.substitute(sym, a);
+
+        upper -= &lower;
+        upper.simplify();
+        upper
     }
-}
 
-impl Neg for Poly {
-    type Output = Self;
+    /// The definite integral of a polynomial univariate in `sym` between the
+    /// rational bounds `lower = (num, den)` and `upper`, as an exactly reduced
+    /// fraction `(num, den)` with `den > 0`. Coeffs are read exactly (see `gcd`).
+    ///
+    /// x2 from (0, 1) to (1, 1) => (1, 3)
+    ///
+    /// `None` if any other symbol is present, a bound has a zero denominator,
+    /// an intermediate result overflows the `i128`s of the exact arithmetic,
+    /// or the result doesn't fit in `i64`s.
+    pub fn integrate_definite_exact(
+        &self,
+        sym: &str,
+        lower: (i64, i64),
+        upper: (i64, i64),
+    ) -> Option<(i64, i64)> {
+        if lower.1 == 0 || upper.1 == 0 {
+            return None;
+        }
 
-    fn neg(mut self) -> Self::Output {
-        for term in &mut self.0 {
-            term.coeff *= -1.;
+        // antiderivative coeffs by power, c x^k => c/(k+1) x^(k+1)
+        let antiderivative = std::iter::once(Some(RatCoeff::ZERO))
+            .chain(
+                self.to_rat_coeffs(sym)?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(k, c)| c.checked_div(RatCoeff::from_integer(k as i128 + 1))),
+            )
+            .collect::<Option<Vec<_>>>()?;
+        let at = |(num, den): (i64, i64)| {
+            let x = RatCoeff::new(num as i128, den as i128);
+            antiderivative
+                .iter()
+                .rev()
+                .try_fold(RatCoeff::ZERO, |acc, c| acc.checked_mul(x)?.checked_add(*c))
+        };
+
+        let res = at(upper)?.checked_sub(at(lower)?)?;
+        Some((res.num().try_into().ok()?, res.den().try_into().ok()?))
+    }
+
+    /// The signed area `∫_a^b (self - other)` between two curves in `sym`.
+    /// Regions where `other` lies above `self` count negatively,
+    /// so it is not the geometric area when the curves cross inside `[a, b]`.
+    ///
+    /// x and x2 from 0 to 1 => 1/6
+    ///
+    /// `None` if any other symbol is left after integrating.
+    pub fn area_between(&self, other: &Poly, sym: &str, a: f64, b: f64) -> Option<f64> {
+        let diff = self.clone() - other.clone();
+        let area = diff.integrate_definite(sym, a, b);
+        match area.0.as_slice() {
+            [] => Some(0.),
+            [term] if term.vars.is_empty() => Some(term.coeff),
+            _ => None,
         }
-        self
     }
-}
 
-impl Sub for Poly {
-    type Output = Self;
+    /// Indefinite integral in `sym` plus the symbolic constant of integration
+    /// `const_sym`, which differentiates away again.
+    ///
+    /// x wrt x with C => 0.5x2 + C
+    pub fn integrate_with_constant(&self, sym: &str, const_sym: &str) -> Poly {
+        let mut res = self.clone();
+        res.integrate(sym);
+        res.0.push(PolyTerm {
+            coeff: 1.,
+            vars: vec![PolyVar {
+                sym: const_sym.to_string(),
+                deg: 1,
+            }],
+        });
+        res.simplify();
+        res
+    }
 
-    fn sub(mut self, rhs: Self) -> Self::Output {
-        self.0.extend(rhs.neg().0);
-        self
+    /// Detects whether a univariate polynomial in `sym` is an exact `k`th power
+    /// (`k >= 2`) of a lower degree polynomial and returns the base and `k`.
+    ///
+    /// x2 + 2x + 1 => Some((x + 1, 2))
+    ///
+    /// The largest possible exponent is preferred, so `(x + 1)^4` is reported
+    /// with `k = 4` rather than as the square of `(x + 1)^2`.
+    pub fn as_perfect_power(&self, sym: &str) -> Option<(Poly, usize)> {
+        let coeffs = self.to_coeffs(sym)?;
+        let n = coeffs.len().checked_sub(1)?;
+
+        (2..=n)
+            .rev()
+            .filter(|k| n.is_multiple_of(*k))
+            .find_map(|k| dense_root(&coeffs, k).map(|base| (Poly::from_coeffs(sym, &base), k)))
     }
-}
 
-impl SubAssign for Poly {
-    fn sub_assign(&mut self, rhs: Self) {
-        self.0.extend(rhs.neg().0);
+    /// The square root of a univariate polynomial in `sym` that is an exact
+    /// square, the one with a positive leading coeff. `None` if there is none
+    /// or another symbol is present.
+    ///
+    /// x2 + 2x + 1 => Some(x + 1)
+    /// x2 + 1      => None
+    pub fn sqrt(&self, sym: &str) -> Option<Poly> {
+        let coeffs = self.to_coeffs(sym)?;
+        match coeffs.is_empty() {
+            true => Some(Poly::zero()),
+            false => dense_root(&coeffs, 2).map(|root| Poly::from_coeffs(sym, &root)),
+        }
     }
-}
 
-impl Mul<&Self> for &Poly {
-    type Output = Poly;
+    /// Samples a polynomial that is univariate in `sym` at `n` evenly spaced
+    /// points from `start` to `stop` (both inclusive), returning `(x, y)` pairs.
+    ///
+    /// `None` if any symbol other than `sym` is present.
+    pub fn eval_grid(&self, sym: &str, start: f64, stop: f64, n: usize) -> Option<Vec<(f64, f64)>> {
+        let coeffs = self.to_coeffs(sym)?;
+        let step = match n {
+            0 | 1 => 0.,
+            _ => (stop - start) / (n - 1) as f64,
+        };
 
-    fn mul(self, rhs: &Self) -> Self::Output {
-        let mut res = Poly(vec![]);
-        for term1 in &self.0 {
-            for term2 in &rhs.0 {
-                let term = PolyTerm {
-                    coeff: term1.coeff * term2.coeff,
-                    vars: {
-                        let mut vars = vec![];
-                        vars.extend(term1.vars.clone());
-                        vars.extend(term2.vars.clone());
-                        vars
-                    },
-                };
-                res.0.push(term);
-            }
-        }
-        res
+        Some(
+            (0..n)
+                .map(|i| start + step * i as f64)
+                .map(|x| (x, horner(&coeffs, x)))
+                .collect(),
+        )
     }
-}
 
-impl Display for Poly {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let [first, rest @ ..] = self.0.as_slice() {
-            write!(f, "{}", first)?;
+    /// The samples of `eval_grid` as CSV for plotting tools: a `sym,y` header
+    /// line, then one `x,y` row per point. Numbers are printed in full (the
+    /// shortest form that reads back as the same `f64`), and a symbol that
+    /// needs it is quoted. Every line ends with a newline.
+    ///
+    /// x2 from 0 to 1 with n 3 => "x,y\n0,0\n0.5,0.25\n1,1\n"
+    ///
+    /// `None` if any symbol other than `sym` is present.
+    pub fn to_csv(&self, sym: &str, start: f64, stop: f64, n: usize) -> Option<String> {
+        let points = self.eval_grid(sym, start, stop, n)?;
 
-            for term in rest {
-                write!(f, " ")?;
-                if term.coeff > 0. {
-                    write!(f, "+")?;
-                }
-                write!(f, "{}", term)?;
-            }
+        let header = match sym.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", sym.replace('"', "\"\"")),
+            false => sym.to_string(),
+        };
+        let mut s = format!("{},y\n", header);
+        for (x, y) in points {
+            s += &format!("{},{}\n", x, y);
         }
+        Some(s)
+    }
 
-        Ok(())
+    /// An enclosure `(min, max)` of the values of a polynomial univariate in
+    /// `sym` over `x` in `[lo, hi]`, by naive interval extension: each term's
+    /// power is bounded by its values at the ends (even powers of an interval
+    /// around zero start at zero), computed by square and multiply, and the
+    /// bounds are rounded outwards after every multiplication and addition, so
+    /// the true range is always contained (though possibly overestimated).
+    ///
+    /// `None` if any other symbol is present.
+    pub fn eval_interval(&self, sym: &str, lo: f64, hi: f64) -> Option<(f64, f64)> {
+        let coeffs = self.to_coeffs(sym)?;
+        let (lo, hi) = (lo.min(hi), lo.max(hi));
+
+        let mut res = (0., 0.);
+        for (k, coeff) in coeffs.iter().enumerate() {
+            let (a, b) = (powu_bounds(lo, k), powu_bounds(hi, k));
+            let power = match (k % 2 == 0, lo >= 0., hi <= 0.) {
+                (false, _, _) | (true, true, _) => (a.0, b.1),
+                (true, _, true) => (b.0, a.1),
+                (true, false, false) => (0., a.1.max(b.1)),
+            };
+
+            let term = match *coeff >= 0. {
+                true => (coeff * power.0, coeff * power.1),
+                false => (coeff * power.1, coeff * power.0),
+            };
+            res = (
+                (res.0 + term.0.next_down()).next_down(),
+                (res.1 + term.1.next_up()).next_up(),
+            );
+        }
+        Some(res)
     }
-}
 
-impl Display for PolyTerm {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.coeff != 1. {
-            write!(f, "{}", self.coeff)?;
+    /// Evaluates a polynomial that is univariate in `sym` at every point using
+    /// a subproduct tree: the polynomial is reduced modulo `prod(x - p)` over
+    /// halves of the points, recursively, until each remainder is a constant.
+    /// This beats per point Horner when there are about as many points as the degree.
+    ///
+    /// `None` if any symbol other than `sym` is present.
+    pub fn eval_multipoint(&self, sym: &str, points: &[f64]) -> Option<Vec<f64>> {
+        let coeffs = self.to_coeffs(sym)?;
+        if points.is_empty() {
+            return Some(vec![]);
         }
-        for var in &self.vars {
-            write!(f, "{}", var)?;
+
+        // levels[0] holds (x - p) for every point, each level above
+        // holds the pairwise products of the one below.
+        let mut levels = vec![points.iter().map(|p| vec![-p, 1.]).collect::<Vec<_>>()];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => dense_mul(a, b),
+                    [a] => a.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
         }
-        Ok(())
+
+        let mut rems = vec![dense_div_rem(&coeffs, &levels[levels.len() - 1][0]).1];
+        for level in levels.iter().rev().skip(1) {
+            rems = level
+                .iter()
+                .enumerate()
+                .map(|(i, node)| dense_div_rem(&rems[i / 2], node).1)
+                .collect();
+        }
+
+        Some(
+            rems.into_iter()
+                .map(|rem| rem.first().copied().unwrap_or(0.))
+                .collect(),
+        )
     }
-}
 
-impl Display for PolyVar {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.sym.len() == 1 {
-            true => write!(f, "{}", self.sym)?,
-            false => write!(f, "({})", self.sym)?,
+    /// Evaluates a polynomial univariate in `sym` at the square matrix `m`,
+    /// using Horner's scheme with the constant term as a multiple of the identity.
+    pub fn eval_matrix(&self, sym: &str, m: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, SymbaError> {
+        let n = m.len();
+        if m.iter().any(|row| row.len() != n) {
+            return Err(SymbaError::NotSquare);
         }
-        if self.deg != 1 {
-            write!(f, "{}", self.deg)?;
+        let coeffs = self
+            .to_coeffs(sym)
+            .ok_or_else(|| SymbaError::NotUnivariate {
+                sym: sym.to_string(),
+            })?;
+
+        let mut res = vec![vec![0.; n]; n];
+        for coeff in coeffs.iter().rev() {
+            res = (0..n)
+                .map(|i| {
+                    (0..n)
+                        .map(|j| {
+                            let prod = (0..n).map(|k| res[i][k] * m[k][j]).sum::<f64>();
+                            match i == j {
+                                true => prod + coeff,
+                                false => prod,
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
         }
-        Ok(())
+        Ok(res)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Whether the powers of `sym` are all even, all odd or mixed. Terms
+    /// without `sym` count as power 0 (even), other symbols are parameters.
+    /// Roots of even or odd polynomials come in ± pairs, and over symmetric
+    /// intervals odd ones integrate to 0, even ones to twice the half.
+    /// Zero is `Even`.
+    ///
+    /// x4 + x2 + 1 => Even
+    /// x3y + x => Odd
+    /// x2 + x => Neither
+    pub fn parity(&self, sym: &str) -> Parity {
+        let mut p = self.clone();
+        p.simplify();
 
-    #[test]
-    fn substitute() {
-        let mut p = Poly(vec![
+        let (mut even, mut odd) = (false, false);
+        for term in &p.0 {
+            let deg = term
+                .vars
+                .iter()
+                .find(|var| var.sym == sym)
+                .map_or(0, |var| var.deg);
+            match deg % 2 == 0 {
+                true => even = true,
+                false => odd = true,
+            }
+        }
+
+        match (even, odd) {
+            (true, true) => Parity::Neither,
+            (false, true) => Parity::Odd,
+            _ => Parity::Even,
+        }
+    }
+
+    /// The reversal `x^n p(1/x)` of a polynomial univariate in `sym` of degree
+    /// `n`, i.e. its coeffs in reverse order. Its roots are the reciprocals of
+    /// the original's (non zero) roots, and `p` is palindromic when `p` equals
+    /// its reversal.
+    ///
+    /// 2x2 + 3x + 5 => 5x2 + 3x + 2
+    ///
+    /// `None` if any other symbol is present.
+    pub fn reverse_coeffs(&self, sym: &str) -> Option<Poly> {
+        let mut coeffs = self.to_coeffs(sym)?;
+        coeffs.reverse();
+        Some(Poly::from_coeffs(sym, &coeffs))
+    }
+
+    /// Polynomial long division in `sym`, returning `(quotient, remainder)`
+    /// such that `self == divisor * quotient + remainder` and the remainder's
+    /// degree is less than the divisor's.
+    ///
+    /// `None` if the divisor is zero or either polynomial has symbols other than `sym`.
+    pub fn div_rem(&self, divisor: &Poly, sym: &str) -> Option<(Poly, Poly)> {
+        let a = self.to_coeffs(sym)?;
+        let b = divisor.to_coeffs(sym)?;
+        if b.is_empty() {
+            return None;
+        }
+
+        let (q, r) = dense_div_rem(&a, &b);
+        Some((Poly::from_coeffs(sym, &q), Poly::from_coeffs(sym, &r)))
+    }
+
+    /// `div_rem`, followed by a check that `divisor * quotient + remainder`
+    /// is within `eps` of `self` coeff by coeff (see `approx_eq`), so rounding
+    /// gone wrong in an ill conditioned division surfaces as an error.
+    ///
+    /// Errors with `DivisionByZero` for a zero divisor, `NotUnivariate` if
+    /// another symbol is present, and `InexactDivision` with the residual
+    /// `divisor * quotient + remainder - self` if the check fails.
+    pub fn checked_div_rem(
+        &self,
+        divisor: &Poly,
+        sym: &str,
+        eps: f64,
+    ) -> Result<(Poly, Poly), SymbaError> {
+        if self.to_coeffs(sym).is_none() || divisor.to_coeffs(sym).is_none() {
+            return Err(SymbaError::NotUnivariate {
+                sym: sym.to_string(),
+            });
+        }
+        let (q, r) = self
+            .div_rem(divisor, sym)
+            .ok_or(SymbaError::DivisionByZero)?;
+
+        // simplify the reconstruction on its own before comparing, merging
+        // everything at once would let its huge terms swallow `self`'s small ones
+        let mut reconstructed = divisor * &q;
+        reconstructed += &r;
+        reconstructed.simplify();
+        if reconstructed.approx_eq(self, eps) {
+            return Ok((q, r));
+        }
+
+        let residual = reconstructed
+            .monomial_diff(self, 0.)
+            .into_iter()
+            .map(|(vars, a, b)| PolyTerm { coeff: a - b, vars })
+            .collect();
+        Err(SymbaError::InexactDivision { residual })
+    }
+
+    /// The mean of the exponents of `sym` weighted by their coeffs, i.e. the
+    /// mean of the random variable whose probability generating function is
+    /// the (normalized) polynomial univariate in `sym`. Equals `p'(1) / p(1)`.
+    ///
+    /// (1/2 + 1/2x)^10 => 5
+    ///
+    /// `None` if any other symbol is present or the coeffs sum to zero.
+    pub fn mean_exponent(&self, sym: &str) -> Option<f64> {
+        let [total, first, _] = self.exponent_moments(sym)?;
+        Some(first / total)
+    }
+
+    /// The variance of the exponents of `sym` weighted by their coeffs, the
+    /// companion of `mean_exponent`.
+    ///
+    /// (1/2 + 1/2x)^10 => 2.5
+    ///
+    /// `None` in the same cases as `mean_exponent`.
+    pub fn variance_exponent(&self, sym: &str) -> Option<f64> {
+        let [total, first, second] = self.exponent_moments(sym)?;
+        let mean = first / total;
+        Some(second / total - mean * mean)
+    }
+
+    /// `[sum(c_k), sum(k c_k), sum(k^2 c_k)]` over the coeffs by power
+    fn exponent_moments(&self, sym: &str) -> Option<[f64; 3]> {
+        let mut moments = [0.; 3];
+        for (k, coeff) in self.to_coeffs(sym)?.into_iter().enumerate() {
+            let k = k as f64;
+            moments[0] += coeff;
+            moments[1] += k * coeff;
+            moments[2] += k * k * coeff;
+        }
+        (moments[0] != 0.).then_some(moments)
+    }
+
+    /// The first `k` power sums `[sum(r), sum(r^2), .., sum(r^k)]` of the roots
+    /// `r` of a polynomial univariate in `sym`, computed from the coefficients
+    /// with Newton's identities (the roots are never found).
+    ///
+    /// `None` for the zero polynomial or if any other symbol is present.
+    pub fn power_sums(&self, sym: &str, k: usize) -> Option<Vec<f64>> {
+        let coeffs = self.to_coeffs(sym)?;
+        let n = coeffs.len().checked_sub(1)?;
+
+        // monic form x^n + c1 x^(n-1) + .. + cn, with cj = 0 for j > n
+        let c = |j: usize| match j <= n {
+            true => coeffs[n - j] / coeffs[n],
+            false => 0.,
+        };
+
+        // pj + c1 p(j-1) + .. + c(j-1) p1 + j cj = 0
+        let mut sums = Vec::<f64>::with_capacity(k);
+        for j in 1..=k {
+            let sum = (1..j).map(|i| c(i) * sums[j - i - 1]).sum::<f64>();
+            sums.push(-(sum + j as f64 * c(j)));
+        }
+        Some(sums)
+    }
+
+    /// The sum of the roots (with multiplicity) of a polynomial univariate in
+    /// `sym`, `-a(n-1) / an` by Vieta's formulas.
+    pub fn roots_sum(&self, sym: &str) -> Result<f64, SymbaError> {
+        let coeffs = self.vieta_coeffs(sym)?;
+        let n = coeffs.len() - 1;
+        Ok(-coeffs[n - 1] / coeffs[n])
+    }
+
+    /// The product of the roots (with multiplicity) of a polynomial univariate
+    /// in `sym`, `(-1)^n a0 / an` by Vieta's formulas.
+    pub fn roots_product(&self, sym: &str) -> Result<f64, SymbaError> {
+        let coeffs = self.vieta_coeffs(sym)?;
+        let n = coeffs.len() - 1;
+        let sign = match n % 2 {
+            0 => 1.,
+            _ => -1.,
+        };
+        Ok(sign * coeffs[0] / coeffs[n])
+    }
+
+    /// `to_coeffs`, requiring degree >= 1
+    fn vieta_coeffs(&self, sym: &str) -> Result<Vec<f64>, SymbaError> {
+        let coeffs = self
+            .to_coeffs(sym)
+            .ok_or_else(|| SymbaError::NotUnivariate {
+                sym: sym.to_string(),
+            })?;
+        match coeffs.len() < 2 {
+            true => Err(SymbaError::ConstantPolynomial),
+            false => Ok(coeffs),
+        }
+    }
+
+    /// The forward difference `p(x + 1) - p(x)` in `sym`, the discrete
+    /// analogue of the derivative and the inverse of `summation`.
+    ///
+    /// x2 => 2x + 1
+    pub fn finite_difference(&self, sym: &str) -> Poly {
+        let shifted = Poly(vec![
             PolyTerm {
                 coeff: 1.,
                 vars: vec![PolyVar {
-                    sym: "x".to_string(),
-                    deg: 2,
+                    sym: sym.to_string(),
+                    deg: 1,
                 }],
             },
             PolyTerm {
-                coeff: -2.,
-                vars: vec![
-                    PolyVar {
-                        sym: "x".to_string(),
-                        deg: 3,
-                    },
-                    PolyVar {
-                        sym: "yy".to_string(),
-                        deg: 1,
-                    },
-                    PolyVar {
-                        sym: "z".to_string(),
-                        deg: 2,
-                    },
-                ],
-            },
-            PolyTerm {
-                coeff: 10.,
-                vars: vec![PolyVar {
-                    sym: "y".to_string(),
-                    deg: 2,
-                }],
+                coeff: 1.,
+                vars: vec![],
             },
         ]);
 
-        println!("{}", p);
-        p.substitute("x", 2.);
-        println!("{}", p);
+        let mut res = self.clone();
+        res.substitute_poly(sym, &shifted);
+        res -= self;
+        res.simplify();
+        res
+    }
+
+    /// The closed form of `p(1) + p(2) + .. + p(n)` as a polynomial in `n`
+    /// (`result_sym`), where `p` is `self` in `index_sym`. Other symbols are
+    /// treated as constants. Each power `k^d` is replaced by faulhaber's
+    /// formula for the sum of `d`th powers, computed exactly.
+    ///
+    /// k wrt k as n => 0.5n2 + 0.5n
+    /// 6k2 + 2 => 2n3 + 3n2 + 3n
+    ///
+    /// None if the exact faulhaber coeffs of some power of `index_sym`
+    /// overflow `i128`.
+    pub fn summation(&self, index_sym: &str, result_sym: &str) -> Option<Poly> {
+        let mut res = Poly(vec![]);
+        for (d, coeff) in self.as_poly_in(index_sym) {
+            res += &coeff * &Poly::from_rat_coeffs(result_sym, &faulhaber(d)?);
+        }
+        res.simplify();
+        Some(res)
+    }
+
+    /// The indefinite sum `p(0) + p(1) + .. + p(x - 1)` in `sym`, the discrete
+    /// analogue of the antiderivative: its `finite_difference` is `self`. The
+    /// constant of summation is fixed by making it vanish at `sym = 0`.
+    /// Other symbols are treated as constants.
+    ///
+    /// 2x + 1 => x2
+    /// 6x2 => 2x3 - 3x2 + x
+    ///
+    /// None when `summation` overflows.
+    pub fn antidifference(&self, sym: &str) -> Option<Poly> {
+        // sum of p(1..=x) minus p(x), plus p(0)
+        let mut at_zero = self.clone();
+        at_zero.substitute(sym, 0.);
+
+        let mut res = self.summation(sym, sym)?;
+        res -= self;
+        res += at_zero;
+        res.simplify();
+        Some(res)
+    }
+
+    /// Inverse of `power_sums`: the monic polynomial in `sym` whose roots have
+    /// the given power sums. Its degree is `sums.len()`.
+    pub fn from_power_sums(sym: &str, sums: &[f64]) -> Poly {
+        let n = sums.len();
+
+        // cj = -(pj + c1 p(j-1) + .. + c(j-1) p1) / j
+        let mut c = vec![1.];
+        for j in 1..=n {
+            let sum = (1..j).map(|i| c[i] * sums[j - i - 1]).sum::<f64>();
+            c.push(-(sums[j - 1] + sum) / j as f64);
+        }
+
+        c.reverse();
+        Poly::from_coeffs(sym, &c)
+    }
+
+    /// The polynomial of degree `degree` in `sym` that best approximates `f`
+    /// on `[a, b]` in the max norm, by the remez exchange algorithm, and its
+    /// max error. Starting from the chebyshev extrema, each of up to `iters`
+    /// rounds solves for the polynomial whose error alternates with equal
+    /// size on a reference of `degree + 2` points, then moves the reference to
+    /// the extrema of that error. It stops early once the equalized error and
+    /// the max error agree.
+    ///
+    /// The error is measured on a fine grid, so the result is as good as that
+    /// grid resolves `f`. Internally `[a, b]` is mapped to `[-1, 1]` to keep
+    /// the linear systems well conditioned.
+    ///
+    /// x2 on [-1, 1] with degree 1 => (0.5, 0.5)
+    ///
+    /// `None` if `[a, b]` is empty or not finite, or a system is singular.
+    pub fn minimax(
+        sym: &str,
+        f: impl Fn(f64) -> f64,
+        a: f64,
+        b: f64,
+        degree: usize,
+        iters: usize,
+    ) -> Option<(Poly, f64)> {
+        if !a.is_finite() || !b.is_finite() || a >= b {
+            return None;
+        }
+        let n = degree;
+        let g = |t: f64| f((a + b) / 2. + (b - a) / 2. * t);
+
+        let samples = 64 * (n + 2);
+        let grid = (0..=samples)
+            .map(|i| -1. + 2. * i as f64 / samples as f64)
+            .collect::<Vec<_>>();
+        let values = grid.iter().map(|t| g(*t)).collect::<Vec<_>>();
+
+        let mut reference = (0..n + 2)
+            .map(|i| -(std::f64::consts::PI * i as f64 / (n + 1) as f64).cos())
+            .collect::<Vec<_>>();
+        let mut best: Option<(Vec<f64>, f64)> = None;
+        for _ in 0..iters.max(1) {
+            // p(t_i) + (-1)^i E = g(t_i) for the coeffs of p and the level E
+            let system = reference
+                .iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    let mut row = (0..=n).map(|j| powu(*t, j)).collect::<Vec<_>>();
+                    row.push(match i % 2 {
+                        0 => 1.,
+                        _ => -1.,
+                    });
+                    row
+                })
+                .collect();
+            let rhs = reference.iter().map(|t| g(*t)).collect();
+            let mut coeffs = solve_dense(system, rhs)?;
+            let level = coeffs.pop()?.abs();
+
+            let errors = grid
+                .iter()
+                .zip(&values)
+                .map(|(t, y)| horner(&coeffs, *t) - y)
+                .collect::<Vec<_>>();
+            let max_err = errors.iter().map(|e| e.abs()).fold(0., f64::max);
+            if best.as_ref().is_none_or(|(_, err)| max_err < *err) {
+                best = Some((coeffs, max_err));
+            }
+
+            if max_err - level <= 1e-9 * max_err {
+                break;
+            }
+            match remez_exchange(&grid, &errors, n + 2) {
+                Some(next) => reference = next,
+                None => break,
+            }
+        }
+
+        // back from t in [-1, 1] to sym in [a, b]
+        let (coeffs, err) = best?;
+        let t = Poly::from_coeffs(sym, &[-(a + b) / (b - a), 2. / (b - a)]);
+        let mut p = Poly::from_coeffs(sym, &coeffs);
+        p.substitute_poly(sym, &t);
+        Some((p, err))
+    }
+
+    /// Reduces modulo the relation `sym^n = 1` by taking every exponent of
+    /// `sym` modulo `n` and merging like terms, so multiplication becomes
+    /// cyclic convolution.
+    ///
+    /// x4y + 2x2 with n = 3 => 2x2 + xy
+    ///
+    /// Panics if `n` is zero.
+    pub fn reduce_mod_cyclic(&mut self, sym: &str, n: usize) -> &mut Self {
+        self.simplify();
+
+        for term in &mut self.0 {
+            for var in &mut term.vars {
+                if var.sym == sym {
+                    var.deg %= n;
+                }
+            }
+        }
+
+        self.simplify()
+    }
+
+    /// Drops every term where the degree of `sym` exceeds `max_deg`, as when
+    /// expanding in a small parameter up to some order.
+    ///
+    /// 1 + x + x2 + x3y with max_deg 2 in x => x2 + x + 1
+    pub fn truncate_in(&mut self, sym: &str, max_deg: usize) -> &mut Self {
+        self.simplify();
+        self.0.retain(|term| {
+            term.vars
+                .iter()
+                .find(|var| var.sym == sym)
+                .is_none_or(|var| var.deg <= max_deg)
+        });
+        self
+    }
+
+    /// The remainder of dividing by `modulus` in `sym`.
+    ///
+    /// `None` in the same cases as `div_rem`.
+    pub fn reduce_mod_poly(&self, modulus: &Poly, sym: &str) -> Option<Poly> {
+        self.div_rem(modulus, sym).map(|(_, r)| r)
+    }
+
+    /// `self^exp` modulo both the univariate polynomial `modulus` and the
+    /// integer `p`, i.e. in `GF(p)[x] / (modulus)` when `p` is prime, by
+    /// square and multiply with a reduction after every step.
+    ///
+    /// x^25 mod (x2 + 2, 5) => x
+    ///
+    /// Coeffs are read exactly (see `gcd`) and mapped into `0..p`, so a
+    /// rational coeff needs a denominator invertible mod `p`. The result has
+    /// coeffs in `0..p`, which are exact in `f64` for `p` up to 2^53.
+    /// `None` if `p < 2`, if `self` and `modulus` are not univariate in the
+    /// same symbol, if `modulus` is zero mod `p` or its leading coeff is not
+    /// invertible mod `p`, or if a denominator is not invertible mod `p`.
+    pub fn pow_mod(&self, exp: u64, modulus: &Poly, p: u64) -> Option<Poly> {
+        if p < 2 {
+            return None;
+        }
+
+        let (base, modulus) = (self.canonical(), modulus.canonical());
+        let mut syms = base
+            .0
+            .iter()
+            .chain(&modulus.0)
+            .flat_map(|term| &term.vars)
+            .map(|var| var.sym.as_str())
+            .collect::<Vec<_>>();
+        syms.sort();
+        syms.dedup();
+        let sym = match syms.as_slice() {
+            [] => "x",
+            [sym] => sym,
+            _ => return None,
+        };
+
+        let to_gf = |poly: &Poly| -> Option<Vec<u64>> {
+            let mut coeffs = poly
+                .to_rat_coeffs(sym)?
+                .into_iter()
+                .map(|c| {
+                    let num = c.num().rem_euclid(p as i128) as u64;
+                    let den = c.den().rem_euclid(p as i128) as u64;
+                    Some(gf_mul(num, gf_inverse(den, p)?, p))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            gf_trim(&mut coeffs);
+            Some(coeffs)
+        };
+
+        let modulus = to_gf(&modulus)?;
+        let lead_inv = gf_inverse(*modulus.last()?, p)?;
+        let rem = |a: Vec<u64>| gf_rem(a, &modulus, lead_inv, p);
+
+        let mut base = rem(to_gf(&base)?);
+        let mut res = rem(vec![1]);
+        let mut exp = exp;
+        while exp > 0 {
+            if exp % 2 == 1 {
+                res = rem(gf_poly_mul(&res, &base, p));
+            }
+            base = rem(gf_poly_mul(&base, &base, p));
+            exp /= 2;
+        }
+
+        let coeffs = res.into_iter().map(|c| c as f64).collect::<Vec<_>>();
+        Some(Poly::from_coeffs(sym, &coeffs))
+    }
+
+    /// The simplified product `self * rhs`. `*` collects all
+    /// `self.len() * rhs.len()` raw terms before they can be merged, this
+    /// merges each product term into its monomial as soon as it is formed, so
+    /// besides the simplified inputs only the terms of the result are ever
+    /// held. For two dense univariate polynomials of degree `n` that is
+    /// `2n + 1` terms instead of `(n + 1)^2`.
+    pub fn mul_simplify(&self, rhs: &Poly) -> Poly {
+        let (lhs, rhs) = (self.canonical(), rhs.canonical());
+
+        let mut m = HashMap::<Vec<PolyVar>, f64>::new();
+        for term1 in &lhs.0 {
+            for term2 in &rhs.0 {
+                let mut term = term1 * term2;
+                *m.entry(std::mem::take(&mut term.vars)).or_insert(0.) += term.coeff;
+            }
+        }
+
+        let mut res = m
+            .into_iter()
+            .map(|(vars, coeff)| PolyTerm { coeff, vars })
+            .collect::<Poly>();
+        res.simplify();
+        res
+    }
+
+    /// The simplified terms keyed by their (sorted) vars, in a deterministic order.
+    pub fn monomial_map(&self) -> BTreeMap<Vec<PolyVar>, f64> {
+        self.canonical()
+            .0
+            .into_iter()
+            .map(|term| (term.vars, term.coeff))
+            .collect()
+    }
+
+    /// A string identifying the simplified polynomial, for keying memo
+    /// tables. Two polynomials get the same key exactly when they simplify to
+    /// the same terms, whatever order or unmerged form they are stored in.
+    /// It is built from borrowed symbols, without cloning or simplifying.
+    /// `Poly` has no `Hash` (or `Eq`) because of its `f64` coeffs, so use the key instead.
+    ///
+    /// Each monomial is its coeff followed by `|len:sym^deg` per var, and
+    /// monomials are joined by `;` in `monomial_map` order. The length prefix
+    /// keeps symbols containing `|`, `^` or `;` from colliding.
+    ///
+    /// 3x2y + yx2 - 1 => -1;4|1:x^2|1:y^1
+    pub fn canonical_key(&self) -> String {
+        let mut monomials = BTreeMap::<Vec<(&str, usize)>, f64>::new();
+        for term in &self.0 {
+            let mut vars = BTreeMap::<&str, usize>::new();
+            for var in &term.vars {
+                *vars.entry(var.sym.as_str()).or_insert(0) += var.deg;
+            }
+            let vars = vars.into_iter().filter(|(_, deg)| *deg != 0).collect();
+            *monomials.entry(vars).or_insert(0.) += term.coeff;
+        }
+
+        monomials
+            .into_iter()
+            .filter(|(_, coeff)| *coeff != 0.)
+            .map(|(vars, coeff)| {
+                let mut s = coeff.to_string();
+                for (sym, deg) in vars {
+                    s += &format!("|{}:{}^{}", sym.len(), sym, deg);
+                }
+                s
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// Whether `self` lies in the ideal generated by `basis`, i.e. is a
+    /// combination `sum(h_i g_i)` of its elements with polynomial `h_i`.
+    /// `basis` is turned into a Gröbner basis under `order` first (see
+    /// `groebner_basis`, including its numeric caveats), since only then does
+    /// the remainder of multivariate division decide membership.
+    ///
+    /// x2 - 1 in (x - 1) => true
+    /// x in (x - 1) => false
+    /// 1e-10x in (y) => false
+    pub fn in_ideal(&self, basis: &[Poly], order: MonomialOrder) -> bool {
+        normal_form(self, &groebner_basis(basis, order), order)
+            .0
+            .is_empty()
+    }
+
+    /// The monomials whose coeffs differ by more than `eps` between the two
+    /// simplified polynomials, as `(vars, coeff in self, coeff in other)` in
+    /// `monomial_map` order. A monomial missing on one side has coeff 0 there.
+    ///
+    /// x2 + 2x vs x2 + 3 => [(x, 2, 0), (1, 0, 3)]
+    pub fn monomial_diff(&self, other: &Poly, eps: f64) -> Vec<(Vec<PolyVar>, f64, f64)> {
+        let (left, right) = (self.monomial_map(), other.monomial_map());
+        let mut monomials = left.keys().chain(right.keys()).collect::<Vec<_>>();
+        monomials.sort();
+        monomials.dedup();
+
+        monomials
+            .into_iter()
+            .map(|vars| {
+                let coeff = |m: &BTreeMap<Vec<PolyVar>, f64>| m.get(vars).copied().unwrap_or(0.);
+                (vars.clone(), coeff(&left), coeff(&right))
+            })
+            // NaN never matches, equal infinities do
+            .filter(|(_, a, b)| a != b && ((a - b).abs() > eps || (a - b).is_nan()))
+            .collect()
+    }
+
+    /// Whether the simplified polynomials have the same monomials with coeffs
+    /// within `eps` of each other (a monomial missing on one side counts as 0).
+    pub fn approx_eq(&self, other: &Poly, eps: f64) -> bool {
+        self.monomial_diff(other, eps).is_empty()
+    }
+
+    /// The monic greatest common divisor of two polynomials univariate in
+    /// `sym`, computed with exact `RatCoeff` arithmetic so no floating point
+    /// error accumulates during euclid's algorithm.
+    ///
+    /// gcd(6x2 - 6, 4x - 4) => x - 1
+    ///
+    /// The coeffs are read exactly with `RatCoeff::from_f64` and only the
+    /// result is converted back to `f64`. `None` if either polynomial has other
+    /// symbols or a coeff that can't be read exactly, or if the exact
+    /// arithmetic overflows its `i128`s. Coeffs like `0.1`, whose exact value
+    /// has a denominator of `2^55`, get there after a few steps.
+    pub fn gcd(&self, other: &Poly, sym: &str) -> Option<Poly> {
+        let a = self.to_rat_coeffs(sym)?;
+        let b = other.to_rat_coeffs(sym)?;
+        Some(Poly::from_rat_coeffs(sym, &rational::poly_gcd(&a, &b)?))
+    }
+
+    /// `div_rem` with exact `RatCoeff` arithmetic, see `gcd`. `None` if the
+    /// divisor is zero, and in the same cases as `gcd`.
+    pub fn div_rem_exact(&self, divisor: &Poly, sym: &str) -> Option<(Poly, Poly)> {
+        let a = self.to_rat_coeffs(sym)?;
+        let b = divisor.to_rat_coeffs(sym)?;
+        if b.is_empty() {
+            return None;
+        }
+
+        let (q, r) = rational::div_rem(&a, &b)?;
+        Some((
+            Poly::from_rat_coeffs(sym, &q),
+            Poly::from_rat_coeffs(sym, &r),
+        ))
+    }
+
+    /// The rational `c` such that `self / c` has coprime integer coeffs and a
+    /// positive leading coeff. Zero for the zero polynomial.
+    ///
+    /// (1/2)x + (1/3) => 1/6
+    /// -6x2 + 9x + 3 => -3
+    ///
+    /// `None` if a coeff can't be read exactly or the lcm of the
+    /// denominators overflows (see `gcd`).
+    pub fn content(&self) -> Option<RatCoeff> {
+        let p = self.canonical();
+        let coeffs =
+            p.0.iter()
+                .map(|term| RatCoeff::from_f64(term.coeff))
+                .collect::<Option<Vec<_>>>()?;
+
+        let lead = match coeffs.first() {
+            Some(lead) => lead,
+            None => return Some(RatCoeff::ZERO),
+        };
+
+        let num = coeffs.iter().fold(0, |acc, c| rational::gcd(acc, c.num()));
+        let den = coeffs
+            .iter()
+            .try_fold(1, |acc, c| rational::checked_lcm(acc, c.den()))?;
+        RatCoeff::checked_new(lead.num().signum() * num, den)
+    }
+
+    /// Scales the simplified polynomial by the lcm of its coeffs' denominators
+    /// so every coeff becomes an integer. Returns the scaled polynomial and
+    /// the factor. Denominators are recognized like `to_fraction_string` does
+    /// (up to 1000, within floating point noise), coeffs that are no such
+    /// fraction don't contribute and are just scaled along. So do fractions
+    /// whose denominator would push the lcm past an `i128`, whatever is left
+    /// of those denominators stays in the scaled coeff.
+    ///
+    /// 1/2x + 1/3 => (3x + 2, 6)
+    pub fn clear_denominators(&self) -> (Poly, f64) {
+        let mut p = self.canonical();
+        let fractions =
+            p.0.iter()
+                .map(|term| RatCoeff::approximate(term.coeff, MAX_FRACTION_DEN))
+                .collect::<Vec<_>>();
+        let lcm = fractions.iter().flatten().fold(1, |acc, r| {
+            rational::checked_lcm(acc, r.den()).unwrap_or(acc)
+        });
+
+        for (term, fraction) in p.0.iter_mut().zip(fractions) {
+            let exact = fraction
+                .filter(|r| lcm % r.den() == 0)
+                .and_then(|r| r.num().checked_mul(lcm / r.den()));
+            term.coeff = match exact {
+                Some(num) => num as f64,
+                None => term.coeff * lcm as f64,
+            };
+        }
+        (p, lcm as f64)
+    }
+
+    /// Splits the simplified polynomial into `(c, p)` with `self == c * p`,
+    /// where `c` is the gcd of the coeffs when they are all integers, negated
+    /// when they are all negative so `p` reads with plus signs. Unlike
+    /// `content`, the leading coeff may stay negative when the signs are
+    /// mixed, and any non integer coeff makes the factor 1.
+    ///
+    /// 6x2 + 9x + 3  => (3, 2x2 + 3x + 1)
+    /// -4x - 6       => (-2, 2x + 3)
+    /// -4x + 6       => (2, -2x + 3)
+    /// 1.5x + 3      => (1, 1.5x + 3)
+    pub fn factor_out_content(&self) -> (f64, Poly) {
+        let mut p = self.canonical();
+        let integers =
+            p.0.iter()
+                .map(
+                    |term| match term.coeff.fract() == 0. && term.coeff.abs() < i64::MAX as f64 {
+                        true => Some(term.coeff as i128),
+                        false => None,
+                    },
+                )
+                .collect::<Option<Vec<_>>>();
+        let integers = match integers {
+            Some(integers) if !integers.is_empty() => integers,
+            _ => return (1., p),
+        };
+
+        let gcd = integers.iter().fold(0, |acc, c| rational::gcd(acc, *c));
+        let factor = match integers.iter().all(|c| *c < 0) {
+            true => -gcd,
+            false => gcd,
+        };
+        for (term, c) in p.0.iter_mut().zip(integers) {
+            term.coeff = (c / factor) as f64;
+        }
+        (factor as f64, p)
+    }
+
+    /// `self / content`, the polynomial with coprime integer coeffs and a
+    /// positive leading coeff.
+    pub fn primitive_part(&self) -> Option<Poly> {
+        let content = self.content()?;
+        if content.is_zero() {
+            return Some(Poly(vec![]));
+        }
+
+        let mut p = self.canonical();
+        for term in &mut p.0 {
+            term.coeff = RatCoeff::from_f64(term.coeff)?
+                .checked_div(content)?
+                .to_f64();
+        }
+        Some(p)
+    }
+
+    /// Whether a polynomial univariate in `sym` splits into lower degree
+    /// factors over the rationals.
+    ///
+    /// x2 - 5x + 6 => Some(true), it is (x - 2)(x - 3)
+    /// x2 + 1 => Some(false)
+    ///
+    /// A quadratic is decided exactly by checking that its discriminant is
+    /// the square of a rational. For higher degrees a rational root means it
+    /// certainly factors; without one a cubic is certainly irreducible, but a
+    /// quartic or higher may still split into irreducible quadratics (or
+    /// bigger), which this does not detect, so `false` is only a heuristic there.
+    /// Constants and linear polynomials give `false`.
+    /// The coeffs are read exactly (see `gcd`), so a rounded `1. / 9.` is not `1/9`.
+    ///
+    /// `None` if other symbols are present, the coeffs can't be read exactly,
+    /// the exact arithmetic overflows or the rational roots are out of reach
+    /// (see `rational_root_candidates`).
+    pub fn factors_over_rationals(&self, sym: &str) -> Option<bool> {
+        let coeffs = self.to_rat_coeffs(sym)?;
+
+        match coeffs.as_slice() {
+            [] | [_] | [_, _] => Some(false),
+            [c, b, a] => {
+                let four_ac = RatCoeff::from_integer(4).checked_mul(*a)?.checked_mul(*c)?;
+                let disc = b.checked_mul(*b)?.checked_sub(four_ac)?;
+                let is_square = |n: i128| n >= 0 && n.isqrt() * n.isqrt() == n;
+                Some(is_square(disc.num()) && is_square(disc.den()))
+            }
+            _ => Some(!self.rational_roots(sym)?.is_empty()),
+        }
+    }
+
+    /// Whether a polynomial univariate in `sym` is irreducible over the
+    /// rationals, when that can be decided cheaply:
+    /// - degree 1 is irreducible, and constants (units) and zero are not
+    /// - degree 2 and 3 are irreducible exactly when there is no rational root
+    /// - higher degrees with a rational root are reducible, and ones passing
+    ///   eisenstein's criterion (for the coeffs or the reversed coeffs) are irreducible
+    ///
+    /// x2 - 2 => Some(true)
+    /// x2 - 4 => Some(false)
+    /// x4 + 1 => None
+    ///
+    /// `None` otherwise, or if other symbols are present or the coeffs can't
+    /// be read exactly (see `gcd`), or if the leading or constant coeff is
+    /// too big to factor by trial division (see `rational_root_candidates`),
+    /// as for `x3 + 1e30`.
+    pub fn is_irreducible_q(&self, sym: &str) -> Option<bool> {
+        let coeffs = self.integer_coeffs(sym)?;
+        if coeffs.len() <= 2 {
+            return Some(coeffs.len() == 2);
+        }
+
+        let has_root = !self.rational_roots(sym)?.is_empty();
+        if has_root || coeffs.len() <= 4 {
+            return Some(!has_root);
+        }
+
+        let mut reversed = coeffs.clone();
+        reversed.reverse();
+        (eisenstein(&coeffs)? || eisenstein(&reversed)?).then_some(true)
+    }
+
+    /// Every `p/q` allowed by the rational root theorem for a polynomial
+    /// univariate in `sym`: `p` divides the lowest non zero coeff and `q` the
+    /// leading one (after scaling to coprime integers), plus `0` if the
+    /// constant term is zero. Sorted ascending without duplicates.
+    ///
+    /// The divisors are found by trial division, so both of those integers
+    /// must be at most `10^12` in absolute value, and there may be at most
+    /// `100000` candidates. Beyond that, as for `x3 + 1e30`, this gives `None`
+    /// rather than run (practically) forever.
+    ///
+    /// `None` also for the zero polynomial, other symbols, coeffs that can't
+    /// be read exactly or that overflow an `i128` once scaled to integers.
+    pub fn rational_root_candidates(&self, sym: &str) -> Option<Vec<RatCoeff>> {
+        let coeffs = self.integer_coeffs(sym)?;
+        let low = coeffs.iter().position(|c| *c != 0)?;
+
+        let ps = divisors(coeffs[low])?;
+        let qs = divisors(coeffs[coeffs.len() - 1])?;
+        if ps.len() * qs.len() * 2 > MAX_ROOT_CANDIDATES {
+            return None;
+        }
+
+        let mut candidates = match low {
+            0 => vec![],
+            _ => vec![RatCoeff::ZERO],
+        };
+        for p in &ps {
+            for q in &qs {
+                candidates.push(RatCoeff::new(*p, *q));
+                candidates.push(RatCoeff::new(-p, *q));
+            }
+        }
+
+        candidates.sort();
+        candidates.dedup();
+        Some(candidates)
+    }
+
+    /// The distinct rational roots, found exactly among `rational_root_candidates`.
+    /// `None` in the same cases, or if evaluating at a candidate overflows.
+    pub fn rational_roots(&self, sym: &str) -> Option<Vec<RatCoeff>> {
+        let coeffs = self.to_rat_coeffs(sym)?;
+        let candidates = self.rational_root_candidates(sym)?;
+
+        let mut roots = vec![];
+        for x in candidates {
+            let value = coeffs
+                .iter()
+                .rev()
+                .try_fold(RatCoeff::ZERO, |acc, c| acc.checked_mul(x)?.checked_add(*c))?;
+            if value.is_zero() {
+                roots.push(x);
+            }
+        }
+        Some(roots)
+    }
+
+    /// `to_rat_coeffs` scaled to coprime integers, `None` if they overflow.
+    fn integer_coeffs(&self, sym: &str) -> Option<Vec<i128>> {
+        let coeffs = self.to_rat_coeffs(sym)?;
+        let den = coeffs
+            .iter()
+            .try_fold(1, |acc, c| rational::checked_lcm(acc, c.den()))?;
+        let nums = coeffs
+            .iter()
+            .map(|c| c.num().checked_mul(den / c.den()))
+            .collect::<Option<Vec<_>>>()?;
+        let g = nums.iter().fold(0, |acc, n| rational::gcd(acc, *n));
+        Some(nums.into_iter().map(|n| n / g).collect())
+    }
+
+    /// multivariate division that is expected to leave no remainder,
+    /// dividing leading terms (in canonical order) until nothing is left.
+    /// `None` if some leading term is not divisible.
+    fn exact_div(&self, divisor: &Poly) -> Option<Poly> {
+        let divisor = divisor.canonical();
+        let lead = divisor.0.first()?;
+
+        let mut quotient = Poly(vec![]);
+        let mut rem = self.canonical();
+        while let Some(term) = rem.0.first().cloned() {
+            let factor = Poly(vec![term.divide(lead)?]);
+            rem -= &factor * &divisor;
+            // the leading terms cancel exactly in theory,
+            // don't let floating point error keep them around
+            rem.0.retain(|t| {
+                let mut t = t.clone();
+                t.normalize_vars();
+                t.vars != term.vars
+            });
+            rem.simplify();
+            quotient += factor;
+        }
+
+        quotient.simplify();
+        Some(quotient)
+    }
+
+    /// `to_coeffs` read exactly as `RatCoeff`s
+    fn to_rat_coeffs(&self, sym: &str) -> Option<Vec<RatCoeff>> {
+        self.to_coeffs(sym)?
+            .into_iter()
+            .map(RatCoeff::from_f64)
+            .collect()
+    }
+
+    fn from_rat_coeffs(sym: &str, coeffs: &[RatCoeff]) -> Poly {
+        let coeffs = coeffs.iter().map(RatCoeff::to_f64).collect::<Vec<_>>();
+        Poly::from_coeffs(sym, &coeffs)
+    }
+
+    /// The vertices of the newton polytope, the convex hull of the exponent
+    /// vectors (aligned with `var_order`, see `exponent_vectors`) of the
+    /// simplified terms. Sorted ascending. Works in any dimension: an exponent
+    /// vector is a vertex unless it is a convex combination of the others.
+    ///
+    /// 1 + x3 + y3 + xy + x2y with [x, y] => [[0, 0], [0, 3], [3, 0]]
+    /// (xy is inside the triangle and x2y on its edge)
+    ///
+    /// `None` if a symbol is missing from `var_order`.
+    pub fn newton_polytope(&self, var_order: &[&str]) -> Option<Vec<Vec<usize>>> {
+        let mut points = self
+            .exponent_vectors(var_order)?
+            .into_iter()
+            .map(|(exps, _)| exps)
+            .collect::<Vec<_>>();
+        points.sort();
+
+        let as_f64 = |exps: &Vec<usize>| exps.iter().map(|e| *e as f64).collect::<Vec<_>>();
+        let vertices = (0..points.len())
+            .filter(|i| {
+                let others = (0..points.len())
+                    .filter(|j| j != i)
+                    .map(|j| as_f64(&points[j]))
+                    .collect::<Vec<_>>();
+                !in_convex_hull(&as_f64(&points[*i]), &others)
+            })
+            .map(|i| points[i].clone())
+            .collect();
+        Some(vertices)
+    }
+
+    /// Each simplified term as a dense exponent vector aligned with
+    /// `var_order`, paired with its coeff. Symbols a term lacks get exponent 0.
+    ///
+    /// 3x2z + y with [x, y, z] => [([2, 0, 1], 3), ([0, 1, 0], 1)]
+    ///
+    /// `None` if a symbol is missing from `var_order`.
+    pub fn exponent_vectors(&self, var_order: &[&str]) -> Option<Vec<(Vec<usize>, f64)>> {
+        self.canonical()
+            .0
+            .into_iter()
+            .map(|term| {
+                let mut exps = vec![0; var_order.len()];
+                for var in term.vars {
+                    let i = var_order.iter().position(|sym| *sym == var.sym)?;
+                    exps[i] = var.deg;
+                }
+                Some((exps, term.coeff))
+            })
+            .collect()
+    }
+
+    /// Views the polynomial as univariate in `sym` with polynomial coeffs in
+    /// the remaining symbols. Returns `(power, coeff)` pairs by power desc.,
+    /// skipping zero coeffs.
+    ///
+    /// x2y + x2 + 3xy + 4 in x => [(2, y + 1), (1, 3y), (0, 4)]
+    pub fn as_poly_in(&self, sym: &str) -> Vec<(usize, Poly)> {
+        let mut p = self.clone();
         p.simplify();
-        println!("{}", p);
 
-        let p2 = Poly(vec![]);
+        let mut m = BTreeMap::<usize, Poly>::new();
+        for mut term in p.0 {
+            let deg = match term.vars.iter().position(|var| var.sym == sym) {
+                Some(i) => term.vars.remove(i).deg,
+                None => 0,
+            };
+            m.entry(deg).or_insert(Poly(vec![])).0.push(term);
+        }
 
-        p += p2;
-        // let p3 = p + p2;
+        m.into_iter()
+            .rev()
+            .map(|(deg, mut coeff)| {
+                coeff.simplify();
+                (deg, coeff)
+            })
+            .collect()
+    }
+
+    /// The coeff of the highest power of `sym`, a polynomial in the
+    /// remaining symbols (see `as_poly_in`). Zero for the zero polynomial.
+    ///
+    /// x2y + x + y in x => y
+    pub fn leading_coeff_in(&self, sym: &str) -> Poly {
+        self.as_poly_in(sym)
+            .into_iter()
+            .next()
+            .map(|(_, coeff)| coeff)
+            .unwrap_or(Poly(vec![]))
+    }
+
+    /// Fraction free division of polynomials viewed as univariate in `sym`
+    /// with coeffs in the other symbols. Returns `(q, r)` such that
+    /// `lc^k * self == divisor * q + r`, where `lc` is `divisor.leading_coeff_in(sym)`,
+    /// `k = max(deg self - deg divisor + 1, 0)` (degrees in `sym`) and `r` has
+    /// a lower degree in `sym` than the divisor. Only ring operations are
+    /// used, never a division by a coeff polynomial.
+    ///
+    /// x2 + 1 by 2x + 1 => (2x - 1, 5), as 4(x2 + 1) = (2x + 1)(2x - 1) + 5
+    /// x2y + x + y by xy + y2 in x => (xy2 - y3 + y, y5), with lc y and k 2
+    ///
+    /// Panics if the divisor is zero.
+    pub fn pseudo_div_rem(&self, divisor: &Poly, sym: &str) -> (Poly, Poly) {
+        let divisor = divisor.canonical();
+        let (n, lc) = divisor
+            .as_poly_in(sym)
+            .into_iter()
+            .next()
+            .expect("pseudo_div_rem by the zero polynomial");
+        let power = |deg: usize| PolyTerm {
+            coeff: 1.,
+            vars: vec![PolyVar {
+                sym: sym.to_string(),
+                deg,
+            }],
+        };
+
+        let mut q = Poly(vec![]);
+        let mut r = self.canonical();
+        let mut k = match r.as_poly_in(sym).first() {
+            Some((m, _)) => (m + 1).saturating_sub(n),
+            None => 0,
+        };
+        loop {
+            let (m, lc_r) = match r.as_poly_in(sym).into_iter().next() {
+                Some((m, lc_r)) if m >= n => (m, lc_r),
+                _ => break,
+            };
+            let s = &lc_r * &power(m - n);
+
+            q = &lc * &q;
+            q += &s;
+            q.simplify();
+
+            // the sym^m terms cancel, drop them outright so rounding can't
+            // leave a tiny leading coeff behind and stall the loop
+            r = &lc * &r;
+            r -= &s * &divisor;
+            r = r
+                .as_poly_in(sym)
+                .into_iter()
+                .filter(|(deg, _)| *deg < m)
+                .map(|(deg, coeff)| &coeff * &power(deg))
+                .sum();
+            r.simplify();
+            k -= 1;
+        }
+
+        let scale = lc.pow(k);
+        let (mut q, mut r) = (&scale * &q, &scale * &r);
+        q.simplify();
+        r.simplify();
+        (q, r)
+    }
+
+    /// The coeff of `sym^power`, a polynomial in the remaining symbols.
+    ///
+    /// x2y + xy + y2, y^1 => x2 + x
+    pub fn coeff_poly_of(&self, sym: &str, power: usize) -> Poly {
+        self.as_poly_in(sym)
+            .into_iter()
+            .find(|(deg, _)| *deg == power)
+            .map(|(_, coeff)| coeff)
+            .unwrap_or(Poly(vec![]))
+    }
+
+    /// Solves `self = 0` for `sym` when `self` is `a * sym + b` with `a` and
+    /// `b` polynomials in the other symbols, returning `-b / a`.
+    ///
+    /// 2xy + 4y2 - 6y in x => -2y + 3
+    ///
+    /// The result has to be a polynomial, so `a` must be a single term that
+    /// divides every term of `b` (a non zero constant always does).
+    /// `None` if `self` is not of degree 1 in `sym` or `-b / a` is not a polynomial.
+    pub fn solve_linear(&self, sym: &str) -> Option<Poly> {
+        let (a, b) = match self.as_poly_in(sym).as_slice() {
+            [(1, a)] => (a.clone(), Poly(vec![])),
+            [(1, a), (0, b)] => (a.clone(), b.clone()),
+            _ => return None,
+        };
+
+        let a = match a.0.as_slice() {
+            [a] => a,
+            _ => return None,
+        };
+
+        let terms =
+            b.0.iter()
+                .map(|term| term.divide(a))
+                .collect::<Option<Vec<_>>>()?;
+        Some(-Poly::new(terms))
+    }
+
+    /// `self^exp` by repeated squaring, simplified. `p^0` is `1`.
+    pub fn pow(&self, exp: usize) -> Poly {
+        let mut res = Poly::one();
+        let mut base = self.canonical();
+        if let [term] = base.0.as_slice() {
+            return Poly::new(vec![term.pow(exp)]);
+        }
+
+        let mut exp = exp;
+
+        while exp > 0 {
+            if exp % 2 == 1 {
+                res = &res * &base;
+                res.simplify();
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = &base * &base;
+                base.simplify();
+            }
+        }
+        res
+    }
+
+    /// Multiplies like `Mul`, but errors with `TooManyTerms` before allocating
+    /// anything if the unsimplified product would have more than `max_terms` terms.
+    pub fn mul_capped(&self, rhs: &Poly, max_terms: usize) -> Result<Poly, SymbaError> {
+        let terms = self.0.len().saturating_mul(rhs.0.len());
+        if terms > max_terms {
+            return Err(SymbaError::TooManyTerms {
+                terms,
+                max: max_terms,
+            });
+        }
+
+        let mut res = self * rhs;
+        res.simplify();
+        Ok(res)
+    }
+
+    /// `pow` where every intermediate product goes through `mul_capped`.
+    pub fn pow_capped(&self, exp: usize, max_terms: usize) -> Result<Poly, SymbaError> {
+        let mut res = Poly::one();
+        let mut base = self.canonical();
+        let mut exp = exp;
+
+        while exp > 0 {
+            if exp % 2 == 1 {
+                res = res.mul_capped(&base, max_terms)?;
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = base.mul_capped(&base, max_terms)?;
+            }
+        }
+        Ok(res)
+    }
+
+    /// Like `pow`, but also accepts negative exponents for constant
+    /// polynomials, `c^-n = 1 / c^n`.
+    ///
+    /// Errors with `NegativeExponent` for a negative power of a non constant
+    /// polynomial (that would be a rational function) and with
+    /// `DivisionByZero` for a negative power of zero.
+    pub fn checked_pow(&self, exp: i64) -> Result<Poly, SymbaError> {
+        if exp >= 0 {
+            return Ok(self.pow(exp as usize));
+        }
+
+        let p = self.canonical();
+        match p.0.as_slice() {
+            [] => Err(SymbaError::DivisionByZero),
+            [term] if term.vars.is_empty() => Ok(Poly(vec![PolyTerm {
+                coeff: 1. / term.coeff.powf(-(exp as f64)),
+                vars: vec![],
+            }])),
+            _ => Err(SymbaError::NegativeExponent),
+        }
+    }
+
+    /// Expands `(a + b)^n` as `sum(C(n, k) a^k b^(n-k))`, computing each power once.
+    pub fn binomial_expand(a: &Poly, b: &Poly, n: usize) -> Poly {
+        let one = Poly::one();
+
+        // b_powers[k] = b^k
+        let mut b_powers = vec![one.clone()];
+        for k in 1..=n {
+            let mut next = &b_powers[k - 1] * b;
+            next.simplify();
+            b_powers.push(next);
+        }
+
+        let mut res = Poly(vec![]);
+        let mut a_power = one;
+        for k in 0..=n {
+            let mut term = &a_power * &b_powers[n - k];
+            for t in &mut term.0 {
+                t.coeff *= binomial(n, k);
+            }
+            res += term;
+
+            a_power = &a_power * a;
+            a_power.simplify();
+        }
+        res.simplify();
+        res
+    }
+
+    /// Expands `(x1 + x2 + .. + xm)^n` directly from the multinomial coeffs
+    /// `n! / (k1! k2! .. km!)`, without any intermediate products.
+    pub fn multinomial(syms: &[&str], n: usize) -> Poly {
+        fn go(syms: &[&str], n: usize, coeff: f64, vars: &mut Vec<PolyVar>, res: &mut Poly) {
+            match syms {
+                [] => {}
+                [last] => {
+                    vars.push(PolyVar {
+                        sym: last.to_string(),
+                        deg: n,
+                    });
+                    res.0.push(PolyTerm {
+                        coeff,
+                        vars: vars.clone(),
+                    });
+                    vars.pop();
+                }
+                [first, rest @ ..] => {
+                    for k in 0..=n {
+                        vars.push(PolyVar {
+                            sym: first.to_string(),
+                            deg: k,
+                        });
+                        go(rest, n - k, coeff * binomial(n, k), vars, res);
+                        vars.pop();
+                    }
+                }
+            }
+        }
+
+        let mut res = Poly(vec![]);
+        go(syms, n, 1., &mut vec![], &mut res);
+        res.simplify();
+        res
+    }
+
+    /// Inverse of `as_poly_in`: flattens `sum(coeff * sym^power)`.
+    pub fn from_poly_coeffs(sym: &str, coeffs: &[(usize, Poly)]) -> Poly {
+        let mut p = Poly(vec![]);
+        for (deg, coeff) in coeffs {
+            let power = Poly(vec![PolyTerm {
+                coeff: 1.,
+                vars: vec![PolyVar {
+                    sym: sym.to_string(),
+                    deg: *deg,
+                }],
+            }]);
+            p += &power * coeff;
+        }
+        p.simplify();
+        p
+    }
+
+    /// Dense coeffs of a polynomial that is univariate in `sym`, indexed by power.
+    /// The last entry is the (non zero) leading coeff, so the zero polynomial
+    /// gives an empty vec.
+    ///
+    /// 3x2 + 1 => [1, 0, 3]
+    ///
+    /// `None` if any other symbol is present.
+    pub fn to_coeffs(&self, sym: &str) -> Option<Vec<f64>> {
+        let mut p = self.clone();
+        p.simplify();
+
+        let mut coeffs = vec![0.; p.degree() + 1];
+        for term in &p.0 {
+            match term.vars.as_slice() {
+                [] => coeffs[0] += term.coeff,
+                [var] if var.sym == sym => coeffs[var.deg] += term.coeff,
+                _ => return None,
+            }
+        }
+
+        while coeffs.last() == Some(&0.) {
+            coeffs.pop();
+        }
+
+        Some(coeffs)
+    }
+
+    /// `to_coeffs` into a fixed size array, zero padded after the leading
+    /// coeff. Terms are added straight into the array, so a polynomial that
+    /// fits doesn't allocate. Only a term in another symbol or of too high a
+    /// degree falls back to `to_coeffs`, in case it cancels out.
+    ///
+    /// 3x2 + 1 into [f64; 4] => [1, 0, 3, 0]
+    ///
+    /// Errors with `NotUnivariate` if another symbol is present and with
+    /// `TooManyTerms` if the `degree + 1` coeffs don't fit in `N`.
+    pub fn to_coeffs_array<const N: usize>(&self, sym: &str) -> Result<[f64; N], SymbaError> {
+        let mut coeffs = [0.; N];
+        for term in &self.0 {
+            let deg = term
+                .vars
+                .iter()
+                .filter(|var| var.deg != 0)
+                .try_fold(0, |acc, var| match var.sym == sym {
+                    true => Some(acc + var.deg),
+                    false => None,
+                });
+            match deg {
+                Some(deg) if deg < N => coeffs[deg] += term.coeff,
+                _ => return self.to_coeffs_array_slow(sym),
+            }
+        }
+        Ok(coeffs)
+    }
+
+    /// the allocating path of `to_coeffs_array`, through a simplified copy.
+    fn to_coeffs_array_slow<const N: usize>(&self, sym: &str) -> Result<[f64; N], SymbaError> {
+        let coeffs = self
+            .to_coeffs(sym)
+            .ok_or_else(|| SymbaError::NotUnivariate {
+                sym: sym.to_string(),
+            })?;
+        if coeffs.len() > N {
+            return Err(SymbaError::TooManyTerms {
+                terms: coeffs.len(),
+                max: N,
+            });
+        }
+
+        let mut res = [0.; N];
+        res[..coeffs.len()].copy_from_slice(&coeffs);
+        Ok(res)
+    }
+
+    /// Inverse of `to_coeffs`. Zero entries (including trailing ones) are
+    /// dropped, so `[1, 0, 0]` is the constant `1` with degree 0.
+    pub fn from_coeffs(sym: &str, coeffs: &[f64]) -> Poly {
+        let mut p = Poly(
+            coeffs
+                .iter()
+                .enumerate()
+                .map(|(deg, &coeff)| PolyTerm {
+                    coeff,
+                    vars: vec![PolyVar {
+                        sym: sym.to_string(),
+                        deg,
+                    }],
+                })
+                .collect(),
+        );
+        p.simplify();
+        p
+    }
+
+    /// The 2d analogue of `from_coeffs`: `grid[i][j]` is the coeff of
+    /// `xsym^i ysym^j`. Rows may have different lengths, zero entries are skipped.
+    ///
+    /// [[1, 2], [0, 3]] => 3xy + 2y + 1
+    pub fn from_bivariate_grid(xsym: &str, ysym: &str, grid: &[Vec<f64>]) -> Poly {
+        let mut terms = vec![];
+        for (i, row) in grid.iter().enumerate() {
+            for (j, &coeff) in row.iter().enumerate() {
+                terms.push(PolyTerm {
+                    coeff,
+                    vars: vec![
+                        PolyVar {
+                            sym: xsym.to_string(),
+                            deg: i,
+                        },
+                        PolyVar {
+                            sym: ysym.to_string(),
+                            deg: j,
+                        },
+                    ],
+                });
+            }
+        }
+        Poly::new(terms)
+    }
+
+    /// Inverse of `from_bivariate_grid`: a rectangular grid with one row per
+    /// power of `xsym` and one column per power of `ysym`, up to the highest
+    /// power of each. The zero polynomial gives an empty grid.
+    ///
+    /// 3xy + 2y + 1 => [[1, 2], [0, 3]]
+    ///
+    /// `None` if any other symbol is present.
+    pub fn to_bivariate_grid(&self, xsym: &str, ysym: &str) -> Option<Vec<Vec<f64>>> {
+        let exps = self.exponent_vectors(&[xsym, ysym])?;
+        let rows = exps.iter().map(|(e, _)| e[0] + 1).max().unwrap_or(0);
+        let cols = exps.iter().map(|(e, _)| e[1] + 1).max().unwrap_or(0);
+
+        let mut grid = vec![vec![0.; cols]; rows];
+        for (e, coeff) in exps {
+            grid[e[0]][e[1]] += coeff;
+        }
+        Some(grid)
+    }
+}
+
+/// Reads a system of polynomials `p = 0` that are linear in `vars` as
+/// `A x = b`, where `x` is `vars` in order. Returns `(A, b)`.
+///
+/// [x + 2y - 3, 4y + 1] with vars [x, y] => ([[1, 2], [0, 4]], [3, -1])
+pub fn linear_system_matrix(
+    polys: &[Poly],
+    vars: &[&str],
+) -> Result<(Vec<Vec<f64>>, Vec<f64>), SymbaError> {
+    let mut a = vec![vec![0.; vars.len()]; polys.len()];
+    let mut b = vec![0.; polys.len()];
+
+    for (index, p) in polys.iter().enumerate() {
+        for term in p.canonical().0 {
+            match term.vars.as_slice() {
+                [] => b[index] -= term.coeff,
+                [var] if var.deg == 1 => match vars.iter().position(|sym| *sym == var.sym) {
+                    Some(j) => a[index][j] += term.coeff,
+                    None => return Err(SymbaError::NotLinear { index }),
+                },
+                _ => return Err(SymbaError::NotLinear { index }),
+            }
+        }
+    }
+
+    Ok((a, b))
+}
+
+/// Reduces the fraction `num / den` of polynomials univariate in `sym` by
+/// dividing both by their (exact) `gcd`, and scales so the denominator has
+/// a positive leading coeff.
+///
+/// (x2 - 1) / (-x - 1) => (-x + 1, 1)
+///
+/// `None` if `den` is zero or `gcd` fails.
+pub fn reduce_fraction(num: &Poly, den: &Poly, sym: &str) -> Option<(Poly, Poly)> {
+    if den.canonical().0.is_empty() {
+        return None;
+    }
+
+    let g = num.gcd(den, sym)?;
+    let (mut num, _) = num.div_rem_exact(&g, sym)?;
+    let (mut den, _) = den.div_rem_exact(&g, sym)?;
+
+    if den.leading_coeff() < 0. {
+        num = -num;
+        den = -den;
+    }
+    Some((num, den))
+}
+
+/// The determinant of the matrix whose row `i` holds the `i`th derivatives
+/// (in `sym`) of `polys`. It is identically zero when the polynomials are
+/// linearly dependent.
+///
+/// [1, x, x2] => 2
+pub fn wronskian(polys: &[Poly], sym: &str) -> Poly {
+    let mut row = polys.iter().map(Poly::canonical).collect::<Vec<_>>();
+    let mut matrix = vec![];
+    for _ in 0..polys.len() {
+        let next = row
+            .iter()
+            .map(|p| {
+                let mut p = p.clone();
+                p.differentiate(sym);
+                p
+            })
+            .collect();
+        matrix.push(row);
+        row = next;
+    }
+
+    det(matrix)
+}
+
+/// The determinant of a square matrix of polynomials, simplified.
+///
+/// Uses cofactor expansion up to 3x3 and fraction free Bareiss elimination
+/// above that, where every division by the previous pivot is exact so no
+/// rational functions appear along the way.
+pub fn poly_det(matrix: &[Vec<Poly>]) -> Result<Poly, SymbaError> {
+    if matrix.iter().any(|row| row.len() != matrix.len()) {
+        return Err(SymbaError::NotSquare);
+    }
+    Ok(det(matrix.to_vec()))
+}
+
+/// `matrix` must be square
+fn det(matrix: Vec<Vec<Poly>>) -> Poly {
+    match matrix.len() <= 3 {
+        true => cofactor_det(&matrix),
+        false => bareiss_det(matrix.clone()).unwrap_or_else(|| cofactor_det(&matrix)),
+    }
+}
+
+/// `None` if floating point error made one of the exact divisions fail.
+fn bareiss_det(mut m: Vec<Vec<Poly>>) -> Option<Poly> {
+    let n = m.len();
+    let mut negate = false;
+    let mut prev = Poly::one();
+
+    for row in &mut m {
+        for entry in row {
+            entry.simplify();
+        }
+    }
+
+    for k in 0..n.saturating_sub(1) {
+        if m[k][k].0.is_empty() {
+            match (k + 1..n).find(|i| !m[*i][k].0.is_empty()) {
+                Some(i) => {
+                    m.swap(k, i);
+                    negate = !negate;
+                }
+                None => return Some(Poly(vec![])),
+            }
+        }
+
+        for i in k + 1..n {
+            for j in k + 1..n {
+                let mut num = &m[k][k] * &m[i][j];
+                num -= &m[i][k] * &m[k][j];
+                num.simplify();
+                m[i][j] = num.exact_div(&prev)?;
+            }
+        }
+        prev = m[k][k].clone();
+    }
+
+    let det = m.pop().and_then(|mut row| row.pop()).unwrap_or(prev);
+    Some(match negate {
+        true => -det,
+        false => det,
+    })
+}
+
+/// determinant by cofactor expansion along the first row.
+fn cofactor_det(matrix: &[Vec<Poly>]) -> Poly {
+    let one = Poly::one();
+    let (first, rest) = match matrix.split_first() {
+        Some(split) => split,
+        None => return one,
+    };
+
+    let mut det = Poly(vec![]);
+    for (j, entry) in first.iter().enumerate() {
+        let minor = rest
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(k, _)| *k != j)
+                    .map(|(_, p)| p.clone())
+                    .collect()
+            })
+            .collect::<Vec<Vec<Poly>>>();
+
+        let term = entry * &cofactor_det(&minor);
+        match j % 2 {
+            0 => det += term,
+            _ => det -= term,
+        }
+    }
+    det.simplify();
+    det
+}
+
+impl PolyTerm {
+    /// Whether `self` divides `other`, i.e. every var of `self` appears in
+    /// `other` with at least the same degree. The coeff only has to be non zero.
+    ///
+    /// 2xy divides 3x2yz
+    pub fn divides(&self, other: &PolyTerm) -> bool {
+        let mut this = self.clone();
+        let mut other = other.clone();
+        this.normalize_vars();
+        other.normalize_vars();
+
+        this.coeff != 0.
+            && this.vars.iter().all(|var| {
+                other
+                    .vars
+                    .iter()
+                    .any(|other_var| other_var.sym == var.sym && other_var.deg >= var.deg)
+            })
+    }
+
+    /// `self^exp`: the coeff raised to `exp` and every degree multiplied by
+    /// it, with the vars normalized. `t^0` is the constant `1`.
+    ///
+    /// (2x2y)^3 => 8x6y3
+    pub fn pow(&self, exp: usize) -> PolyTerm {
+        let mut res = PolyTerm {
+            coeff: pow_exact(self.coeff, exp),
+            vars: self
+                .vars
+                .iter()
+                .map(|var| PolyVar {
+                    sym: var.sym.clone(),
+                    deg: var.deg * exp,
+                })
+                .collect(),
+        };
+        res.normalize_vars();
+        res
+    }
+
+    /// The least common multiple: each symbol with the higher of its degrees
+    /// (a symbol missing from one term has degree 0 there). When both coeffs
+    /// are integers the coeff is their (non negative) lcm, otherwise (or if
+    /// the lcm overflows an `i128`) 1.
+    ///
+    /// lcm(4x2y, 6xy3) => 12x2y3
+    pub fn monomial_lcm(&self, other: &PolyTerm) -> PolyTerm {
+        let coeff = self.integer_coeff_with(other, rational::checked_lcm);
+        self.combine_vars(other, usize::max, coeff)
+    }
+
+    /// The greatest common divisor: each symbol with the lower of its degrees,
+    /// so symbols in only one term drop out. When both coeffs are integers the
+    /// coeff is their (positive) gcd, otherwise 1.
+    ///
+    /// gcd(4x2y, 6xy3z) => 2xy
+    pub fn monomial_gcd(&self, other: &PolyTerm) -> PolyTerm {
+        let coeff = self.integer_coeff_with(other, |a, b| Some(rational::gcd(a, b)));
+        self.combine_vars(other, usize::min, coeff)
+    }
+
+    /// `f` of the coeffs if both are (exactly) integers and `f` gives a value, otherwise 1.
+    fn integer_coeff_with(&self, other: &PolyTerm, f: impl Fn(i128, i128) -> Option<i128>) -> f64 {
+        let integer = |coeff: f64| RatCoeff::from_f64(coeff).filter(RatCoeff::is_integer);
+        match (integer(self.coeff), integer(other.coeff)) {
+            (Some(a), Some(b)) => f(a.num(), b.num()).map_or(1., |c| c as f64),
+            _ => 1.,
+        }
+    }
+
+    /// the normalized vars of both terms, merged symbol by symbol with `combine`
+    fn combine_vars(
+        &self,
+        other: &PolyTerm,
+        combine: impl Fn(usize, usize) -> usize,
+        coeff: f64,
+    ) -> PolyTerm {
+        let (mut this, mut other) = (self.clone(), other.clone());
+        this.normalize_vars();
+        other.normalize_vars();
+
+        // (degree in self, degree in other) by symbol
+        let mut degs = BTreeMap::<String, (usize, usize)>::new();
+        for var in this.vars {
+            degs.entry(var.sym).or_default().0 = var.deg;
+        }
+        for var in other.vars {
+            degs.entry(var.sym).or_default().1 = var.deg;
+        }
+
+        let mut res = PolyTerm {
+            coeff,
+            vars: degs
+                .into_iter()
+                .map(|(sym, (deg1, deg2))| PolyVar {
+                    sym,
+                    deg: combine(deg1, deg2),
+                })
+                .collect(),
+        };
+        res.normalize_vars();
+        res
+    }
+
+    /// Monomial division `self / divisor`.
+    /// `None` if `divisor` does not divide `self`.
+    ///
+    /// 6x2yz / 2xy => 3xz
+    pub fn divide(&self, divisor: &PolyTerm) -> Option<PolyTerm> {
+        if !divisor.divides(self) {
+            return None;
+        }
+
+        let mut res = PolyTerm {
+            coeff: self.coeff / divisor.coeff,
+            vars: self.vars.clone(),
+        };
+        res.normalize_vars();
+        let mut divisor = divisor.clone();
+        divisor.normalize_vars();
+
+        for var in &divisor.vars {
+            if let Some(res_var) = res.vars.iter_mut().find(|v| v.sym == var.sym) {
+                res_var.deg -= var.deg;
+            }
+        }
+        res.normalize_vars();
+        Some(res)
+    }
+
+    /// canonicalizes a single term: merges vars with the same symbol, drops
+    /// zero degree vars and sorts by symbol. the coeff is left untouched.
+    /// useful for hand built terms before comparing or displaying them.
+    /// 4y2x0x3y => 4x3y3
+    pub fn normalize_vars(&mut self) -> &mut Self {
+        // a fresh map per term, so no degrees can leak from one term into the next
+        let mut m = HashMap::<String, usize>::new();
+        for var in self.vars.drain(..) {
+            *m.entry(var.sym).or_insert(0) += var.deg;
+        }
+        self.vars = m
+            .into_iter()
+            .filter(|(_, deg)| *deg != 0)
+            .map(|(sym, deg)| PolyVar { sym, deg })
+            .collect();
+        self.vars.sort_by(|var1, var2| var1.sym.cmp(&var2.sym));
+        self
+    }
+
+    fn degree(&self) -> usize {
+        self.vars.iter().map(|var| var.deg).sum()
+    }
+
+    fn weighted_degree(&self, weights: &HashMap<String, usize>) -> usize {
+        self.vars
+            .iter()
+            .map(|var| var.deg * weights.get(&var.sym).copied().unwrap_or(1))
+            .sum()
+    }
+}
+
+/// A Gröbner basis of the ideal generated by `polys` under `order`, by
+/// Buchberger's algorithm: the S-polynomial of every pair of basis elements
+/// is reduced against the basis and added if a non zero remainder is left,
+/// until all pairs reduce to zero. Pairs with coprime leading monomials are
+/// skipped (Buchberger's first criterion).
+///
+/// The result is the reduced basis, unique for the ideal and order: every
+/// element is monic, no leading monomial divides another and no term of an
+/// element is divisible by the others' leading monomials. It is sorted by
+/// leading monomial, biggest first. The zero ideal has the empty basis.
+///
+/// {x2 + y2 - 1, x - y} under Lex => [x - y, y2 - 0.5]
+///
+/// Coeffs are `f64`s, so this is exact only while the arithmetic is
+/// (small integer or dyadic coeffs). Otherwise rounding leaves tiny coeffs
+/// where exact cancellation was due; after each cancellation, coeffs within
+/// 1e-9 of the largest coeff that went into it are treated as zero. The input
+/// itself is never chopped, but this can still go wrong for ill conditioned
+/// systems or coeffs spanning many orders of magnitude.
+pub fn groebner_basis(polys: &[Poly], order: MonomialOrder) -> Vec<Poly> {
+    let mut basis = polys
+        .iter()
+        .map(Poly::canonical)
+        .filter(|p| !p.0.is_empty())
+        .collect::<Vec<_>>();
+    let mut pairs = (0..basis.len())
+        .flat_map(|j| (0..j).map(move |i| (i, j)))
+        .collect::<Vec<_>>();
+
+    while let Some((i, j)) = pairs.pop() {
+        let (lt_i, lt_j) = (
+            leading_term(&basis[i], order).unwrap(),
+            leading_term(&basis[j], order).unwrap(),
+        );
+        let coprime = lt_i
+            .vars
+            .iter()
+            .all(|var| lt_j.vars.iter().all(|other| other.sym != var.sym));
+        if coprime {
+            continue;
+        }
+
+        let rem = normal_form(&s_polynomial(&basis[i], &basis[j], order), &basis, order);
+        if !rem.0.is_empty() {
+            pairs.extend((0..basis.len()).map(|i| (i, basis.len())));
+            basis.push(rem);
+        }
+    }
+
+    // minimal: drop elements whose leading monomial is divisible by another's
+    let mut kept = vec![true; basis.len()];
+    for i in 0..basis.len() {
+        let lt_i = leading_term(&basis[i], order).unwrap();
+        kept[i] = !(0..basis.len())
+            .any(|j| j != i && kept[j] && leading_term(&basis[j], order).unwrap().divides(lt_i));
+    }
+    let mut basis = basis
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(g, kept)| kept.then_some(g))
+        .collect::<Vec<_>>();
+
+    // reduced: each element monic and in normal form wrt the others
+    for i in 0..basis.len() {
+        let others = basis
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, g)| g.clone())
+            .collect::<Vec<_>>();
+        let g = normal_form(&basis[i], &others, order);
+        let lead = leading_term(&g, order).unwrap().coeff;
+        basis[i] = &g
+            * &PolyTerm {
+                coeff: 1. / lead,
+                vars: vec![],
+            };
+    }
+
+    basis.sort_by(|g, h| {
+        order.compare(
+            leading_term(h, order).unwrap(),
+            leading_term(g, order).unwrap(),
+        )
+    });
+    basis
+}
+
+/// the biggest term of a simplified polynomial in `order`, `None` for zero.
+fn leading_term(p: &Poly, order: MonomialOrder) -> Option<&PolyTerm> {
+    p.0.iter().max_by(|t1, t2| order.compare(t1, t2))
+}
+
+/// simplifies, then drops the coeffs `f64` rounding left behind where exact
+/// arithmetic would have cancelled to zero. `scale` is the largest coeff that
+/// went into the cancellation, the rounding is relative to it.
+fn chop(p: &mut Poly, scale: f64) {
+    p.simplify();
+    p.0.retain(|term| term.coeff.abs() > EPSILON * scale);
+}
+
+/// The S-polynomial `m/LT(f) f - m/LT(g) g` of `f` and `g` under `order`,
+/// where `m` is the lcm of their leading monomials (with coeff 1) and the
+/// leading terms `LT` include their coeffs. Both products have leading term
+/// `m`, which cancels (exactly, whatever `f64` rounding says), so the result
+/// is simplified and smaller than `m`. Zero if `f` or `g` is zero.
+///
+/// x3y2 - x2y3 + x, 3x4y + y2 under GrLex => -x3y3 + x2 - 1/3y3
+pub fn s_polynomial(f: &Poly, g: &Poly, order: MonomialOrder) -> Poly {
+    let (f, g) = (f.canonical(), g.canonical());
+    let (f, g) = (&f, &g);
+    let (lt_f, lt_g) = match (leading_term(f, order), leading_term(g, order)) {
+        (Some(lt_f), Some(lt_g)) => (lt_f, lt_g),
+        _ => return Poly(vec![]),
+    };
+    let lcm = PolyTerm {
+        coeff: 1.,
+        ..lt_f.monomial_lcm(lt_g)
+    };
+
+    let mut res = f * &lcm.divide(lt_f).unwrap();
+    let other = g * &lcm.divide(lt_g).unwrap();
+    let scale = res.max_norm().max(other.max_norm());
+    res -= &other;
+    res.simplify();
+    res.0.retain(|term| term.vars != lcm.vars);
+    chop(&mut res, scale);
+    res
+}
+
+/// the remainder of multivariate division of `p` by `basis` in `order`:
+/// leading terms divisible by some basis element's leading term are
+/// cancelled, the rest move to the remainder, until nothing is left.
+fn normal_form(p: &Poly, basis: &[Poly], order: MonomialOrder) -> Poly {
+    let mut p = p.canonical();
+
+    let mut rem = Poly(vec![]);
+    while let Some(lt) = leading_term(&p, order).cloned() {
+        let divisor = basis.iter().find_map(|g| {
+            let factor = lt.divide(leading_term(g, order)?)?;
+            Some((g, factor))
+        });
+        match divisor {
+            Some((g, factor)) => {
+                let product = g * &factor;
+                let scale = p.max_norm().max(product.max_norm());
+                p -= &product;
+                p.simplify();
+                // cancelled exactly, whatever rounding says
+                p.0.retain(|term| term.vars != lt.vars);
+                chop(&mut p, scale);
+            }
+            None => {
+                rem.0.push(lt.clone());
+                p.0.retain(|term| term.vars != lt.vars);
+            }
+        }
+    }
+
+    rem.simplify();
+    rem
+}
+
+/// lexicographic order on sorted var lists.
+/// a higher power of an earlier symbol comes first.
+/// x2 < xy < x < y2
+fn lex_cmp(vars1: &[PolyVar], vars2: &[PolyVar]) -> Ordering {
+    lex_cmp_by(vars1, vars2, |sym1, sym2| sym1.cmp(sym2))
+}
+
+/// `lex_cmp` for var lists sorted by `sym_cmp` instead of alphabetically.
+fn lex_cmp_by(
+    vars1: &[PolyVar],
+    vars2: &[PolyVar],
+    sym_cmp: impl Fn(&str, &str) -> Ordering,
+) -> Ordering {
+    for (var1, var2) in vars1.iter().zip(vars2) {
+        let ord = sym_cmp(&var1.sym, &var2.sym).then_with(|| var2.deg.cmp(&var1.deg));
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    vars2.len().cmp(&vars1.len())
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// the largest denominator a coeff is recognized as a fraction with, see `RatCoeff::approximate`
+const MAX_FRACTION_DEN: i128 = 1000;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.)
+}
+
+/// the largest `|n|` that `divisors` and `prime_factors` trial divide, a
+/// million divisions at most.
+const MAX_TRIAL_DIVISION: i128 = 1_000_000_000_000;
+
+/// the most candidates `rational_root_candidates` will list and evaluate.
+const MAX_ROOT_CANDIDATES: usize = 100_000;
+
+/// the positive divisors of `n` (of `|n|`), `n` must not be zero.
+/// `None` if `|n|` is over `MAX_TRIAL_DIVISION`.
+fn divisors(n: i128) -> Option<Vec<i128>> {
+    let n = n.checked_abs().filter(|n| *n <= MAX_TRIAL_DIVISION)?;
+    let mut small = vec![];
+    let mut large = vec![];
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            small.push(d);
+            if d * d != n {
+                large.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    small.extend(large.into_iter().rev());
+    Some(small)
+}
+
+/// whether `p` is a convex combination of `points` (all of `p`'s dimension):
+/// phase one of the simplex method on `sum(l_i q_i) = p, sum(l_i) = 1, l >= 0`,
+/// starting from one artificial variable per row and using bland's rule so it
+/// can't cycle. `p` is in the hull exactly when the artificials can reach 0.
+fn in_convex_hull(p: &[f64], points: &[Vec<f64>]) -> bool {
+    let (m, rows) = (points.len(), p.len() + 1);
+    let cols = m + rows;
+
+    // tableau [A | I | b], the last row of A and b being the sum(l_i) = 1 constraint
+    let mut t = vec![vec![0.; cols + 1]; rows];
+    for (r, row) in t.iter_mut().enumerate() {
+        for (i, q) in points.iter().enumerate() {
+            row[i] = q.get(r).copied().unwrap_or(1.);
+        }
+        row[m + r] = 1.;
+        row[cols] = p.get(r).copied().unwrap_or(1.);
+    }
+    let mut basis = (m..cols).collect::<Vec<_>>();
+
+    // minimize the sum of the artificials (columns m..)
+    let cost = |j: usize| if j >= m { 1. } else { 0. };
+    loop {
+        let reduced = |j: usize| cost(j) - (0..rows).map(|r| cost(basis[r]) * t[r][j]).sum::<f64>();
+        let entering = match (0..cols).find(|j| reduced(*j) < -EPSILON) {
+            Some(j) => j,
+            None => break,
+        };
+
+        let leaving = (0..rows)
+            .filter(|r| t[*r][entering] > EPSILON)
+            .min_by(|r1, r2| {
+                let ratio = |r: usize| t[r][cols] / t[r][entering];
+                ratio(*r1)
+                    .total_cmp(&ratio(*r2))
+                    .then(basis[*r1].cmp(&basis[*r2]))
+            });
+        // the objective is bounded below by 0, so some row always limits the step
+        let r = match leaving {
+            Some(r) => r,
+            None => break,
+        };
+
+        let pivot = t[r][entering];
+        for x in &mut t[r] {
+            *x /= pivot;
+        }
+        let pivot_row = t[r].clone();
+        for (other, row) in t.iter_mut().enumerate() {
+            let factor = row[entering];
+            if other != r && factor != 0. {
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+        basis[r] = entering;
+    }
+
+    (0..rows).map(|r| cost(basis[r]) * t[r][cols]).sum::<f64>() < EPSILON
+}
+
+/// the distinct prime factors of `|n|` ascending, by trial division. `n` must not be zero.
+/// `None` if `|n|` is over `MAX_TRIAL_DIVISION`.
+fn prime_factors(n: i128) -> Option<Vec<i128>> {
+    let mut n = n.checked_abs().filter(|n| *n <= MAX_TRIAL_DIVISION)?;
+    let mut factors = vec![];
+    let mut d = 2;
+    while d * d <= n {
+        if n % d == 0 {
+            factors.push(d);
+            while n % d == 0 {
+                n /= d;
+            }
+        }
+        d += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    Some(factors)
+}
+
+/// eisenstein's criterion on integer coeffs by power: some prime divides every
+/// coeff but the leading one, and its square doesn't divide the constant.
+/// `None` if the constant is too big to factor, see `prime_factors`.
+fn eisenstein(coeffs: &[i128]) -> Option<bool> {
+    let (lead, rest) = match coeffs.split_last() {
+        Some((lead, rest)) if !rest.is_empty() && rest[0] != 0 => (*lead, rest),
+        _ => return Some(false),
+    };
+
+    Some(
+        prime_factors(rest[0])?
+            .into_iter()
+            .any(|p| rest.iter().all(|c| c % p == 0) && lead % p != 0 && rest[0] % (p * p) != 0),
+    )
+}
+
+/// C(n, k)
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k.min(n - k)).fold(1., |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// coeffs (by power) of faulhaber's `1^d + 2^d + .. + n^d` as a polynomial in `n`:
+/// `1/(d+1) * sum_j C(d+1, j) B_j n^(d+1-j)` with bernoulli numbers `B_1 = +1/2`.
+/// exact, or None once an `i128` overflows.
+/// d = 1 => [0, 1/2, 1/2]
+fn faulhaber(d: usize) -> Option<Vec<RatCoeff>> {
+    // pascal's triangle up to row d + 1
+    let mut pascal: Vec<Vec<i128>> = vec![vec![1]];
+    for m in 1..=d + 1 {
+        let prev = &pascal[m - 1];
+        let row = (0..=m)
+            .map(|j| match j == 0 || j == m {
+                true => Some(1),
+                false => prev[j - 1].checked_add(prev[j]),
+            })
+            .collect::<Option<_>>()?;
+        pascal.push(row);
+    }
+    let choose = |n: usize, k: usize| RatCoeff::from_integer(pascal[n][k]);
+
+    // B_m = -1/(m+1) * sum_{j<m} C(m+1, j) B_j
+    let mut bernoulli = vec![RatCoeff::ONE];
+    for m in 1..=d {
+        let sum = (0..m).try_fold(RatCoeff::ZERO, |acc, j| {
+            acc.checked_add(choose(m + 1, j).checked_mul(bernoulli[j])?)
+        })?;
+        bernoulli.push(
+            sum.checked_neg()?
+                .checked_div(RatCoeff::from_integer(m as i128 + 1))?,
+        );
+    }
+    if d >= 1 {
+        bernoulli[1] = RatCoeff::new(1, 2);
+    }
+
+    let mut coeffs = vec![RatCoeff::ZERO; d + 2];
+    for (j, b) in bernoulli.into_iter().enumerate() {
+        coeffs[d + 1 - j] = choose(d + 1, j)
+            .checked_mul(b)?
+            .checked_div(RatCoeff::from_integer(d as i128 + 1))?;
+    }
+    Some(coeffs)
+}
+
+/// `val^deg` for `substitute` and friends. an integer `val` whose power fits
+/// in an `i128` is raised exactly and rounded once, where `powi` rounds after
+/// every multiplication (10^33 comes out as 1.0000000000000001e33).
+/// anything else falls back to `powu`.
+fn pow_exact(val: f64, deg: usize) -> f64 {
+    if val.fract() == 0. && val.abs() < i64::MAX as f64 {
+        let exact = u32::try_from(deg)
+            .ok()
+            .and_then(|deg| (val as i128).checked_pow(deg));
+        if let Some(n) = exact {
+            return n as f64;
+        }
+    }
+    powu(val, deg)
+}
+
+/// `val^deg` by `powi`, or by `powf` for degrees too big for an `i32`,
+/// which `deg as i32` would wrap into nonsense (often negative) exponents,
+/// with the sign taken from the parity of `deg`, which `deg as f64` can round away.
+fn powu(val: f64, deg: usize) -> f64 {
+    match i32::try_from(deg) {
+        Ok(deg) => val.powi(deg),
+        Err(_) => {
+            let abs = val.abs().powf(deg as f64);
+            match val < 0. && deg % 2 == 1 {
+                true => -abs,
+                false => abs,
+            }
+        }
+    }
+}
+
+/// lower and upper bounds on `val^deg`, by square and multiply with every
+/// product rounded outwards, since `powi` and `powf` aren't correctly rounded.
+fn powu_bounds(val: f64, deg: usize) -> (f64, f64) {
+    // bounds on |val|^deg, both non negative
+    let (mut base, mut res) = ((val.abs(), val.abs()), (1., 1.));
+    let mut deg_left = deg;
+    while deg_left > 0 {
+        let mul = |a: (f64, f64), b: (f64, f64)| {
+            (f64::max((a.0 * b.0).next_down(), 0.), (a.1 * b.1).next_up())
+        };
+        if deg_left % 2 == 1 {
+            res = mul(res, base);
+        }
+        base = mul(base, base);
+        deg_left /= 2;
+    }
+    match val < 0. && deg % 2 == 1 {
+        true => (-res.1, -res.0),
+        false => res,
+    }
+}
+
+/// the next remez reference: the largest error of every run of grid points
+/// where the error keeps its sign, so consecutive picks alternate. surplus
+/// picks are dropped from whichever end has the smaller error. `None` if the
+/// error alternates fewer than `k` times.
+fn remez_exchange(grid: &[f64], errors: &[f64], k: usize) -> Option<Vec<f64>> {
+    let mut extrema: Vec<(f64, f64)> = vec![];
+    for (t, e) in grid.iter().zip(errors) {
+        if *e == 0. || e.is_nan() {
+            continue;
+        }
+        match extrema.last_mut() {
+            Some(last) if last.1.signum() == e.signum() => {
+                if e.abs() > last.1.abs() {
+                    *last = (*t, *e);
+                }
+            }
+            _ => extrema.push((*t, *e)),
+        }
+    }
+
+    if extrema.len() < k {
+        return None;
+    }
+    let mut extrema = &extrema[..];
+    while extrema.len() > k {
+        extrema = match extrema[0].1.abs() < extrema[extrema.len() - 1].1.abs() {
+            true => &extrema[1..],
+            false => &extrema[..extrema.len() - 1],
+        };
+    }
+    Some(extrema.iter().map(|(t, _)| *t).collect())
+}
+
+/// solves the square system `a x = b` by gaussian elimination with partial
+/// pivoting. `None` if it is (numerically) singular.
+fn solve_dense(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for k in 0..n {
+        let pivot = (k..n).max_by(|i, j| a[*i][k].abs().total_cmp(&a[*j][k].abs()))?;
+        if a[pivot][k].abs() < f64::MIN_POSITIVE || !a[pivot][k].is_finite() {
+            return None;
+        }
+        a.swap(k, pivot);
+        b.swap(k, pivot);
+
+        for i in k + 1..n {
+            let factor = a[i][k] / a[k][k];
+            let (above, below) = a.split_at_mut(i);
+            for (x, y) in below[0][k..].iter_mut().zip(&above[k][k..]) {
+                *x -= factor * y;
+            }
+            b[i] -= factor * b[k];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for k in (0..n).rev() {
+        let sum = (k + 1..n).map(|j| a[k][j] * x[j]).sum::<f64>();
+        x[k] = (b[k] - sum) / a[k][k];
+    }
+    Some(x)
+}
+
+fn horner(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().rev().fold(0., |acc, coeff| acc * x + coeff)
+}
+
+/// horner in the smallest symbol present, recursing into the coeffs.
+/// vars of each term must be normalized.
+fn multivariate_horner(terms: Vec<PolyTerm>, bindings: &HashMap<String, f64>) -> f64 {
+    let sym = match terms.iter().filter_map(|term| term.vars.first()).min() {
+        Some(var) => var.sym.clone(),
+        None => return terms.iter().map(|term| term.coeff).sum(),
+    };
+    let x = bindings.get(&sym).copied().unwrap_or(f64::NAN);
+
+    let mut m = BTreeMap::<usize, Vec<PolyTerm>>::new();
+    for mut term in terms {
+        let deg = match term.vars.first() {
+            Some(var) if var.sym == sym => term.vars.remove(0).deg,
+            _ => 0,
+        };
+        m.entry(deg).or_default().push(term);
+    }
+
+    // ((c3 x + c2) x^2 + c0) with the gaps between powers as one powi each
+    let mut acc = 0.;
+    let mut prev = None;
+    for (deg, coeff) in m.into_iter().rev() {
+        if let Some(prev) = prev {
+            acc *= powu(x, prev - deg);
+        }
+        acc += multivariate_horner(coeff, bindings);
+        prev = Some(deg);
+    }
+    acc * powu(x, prev.unwrap_or(0))
+}
+
+// GF(p) helpers for `pow_mod`. dense coeffs indexed by power, each in 0..p.
+
+fn gf_mul(a: u64, b: u64, p: u64) -> u64 {
+    (a as u128 * b as u128 % p as u128) as u64
+}
+
+/// `a + b` mod `p` for `a`, `b` in `0..p`, without overflowing for `p` above 2^63.
+fn gf_add(a: u64, b: u64, p: u64) -> u64 {
+    match a >= p - b {
+        true => a - (p - b),
+        false => a + b,
+    }
+}
+
+/// `a - b` mod `p` for `a`, `b` in `0..p`.
+fn gf_sub(a: u64, b: u64, p: u64) -> u64 {
+    match a >= b {
+        true => a - b,
+        false => a + (p - b),
+    }
+}
+
+/// the inverse of `a` mod `p` by extended euclid, if `a` and `p` are coprime.
+fn gf_inverse(a: u64, p: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (p as i128, a as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    (r0 == 1).then(|| t0.rem_euclid(p as i128) as u64)
+}
+
+fn gf_trim(coeffs: &mut Vec<u64>) {
+    while coeffs.last() == Some(&0) {
+        coeffs.pop();
+    }
+}
+
+fn gf_poly_mul(a: &[u64], b: &[u64], p: u64) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+
+    let mut res = vec![0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] = gf_add(res[i + j], gf_mul(*x, *y, p), p);
+        }
+    }
+    res
+}
+
+/// remainder of `a` by the trimmed non zero `m` whose leading coeff has inverse `lead_inv`.
+fn gf_rem(mut a: Vec<u64>, m: &[u64], lead_inv: u64, p: u64) -> Vec<u64> {
+    let n = m.len() - 1;
+    while a.len() > n {
+        let top = a.len() - 1;
+        let factor = gf_mul(a[top], lead_inv, p);
+        for (j, coeff) in m.iter().enumerate() {
+            let i = top - n + j;
+            a[i] = gf_sub(a[i], gf_mul(factor, *coeff, p), p);
+        }
+        a.pop();
+    }
+    gf_trim(&mut a);
+    a
+}
+
+fn dense_mul(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+
+    let mut res = vec![0.; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] += x * y;
+        }
+    }
+    res
+}
+
+/// `b` must not be zero or have a zero leading coeff.
+/// the remainder is trimmed to its true length.
+fn dense_div_rem(a: &[f64], b: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let lead = b[b.len() - 1];
+    if a.len() < b.len() {
+        return (vec![], a.to_vec());
+    }
+
+    let mut r = a.to_vec();
+    let mut q = vec![0.; a.len() - b.len() + 1];
+    for i in (0..q.len()).rev() {
+        let factor = r[i + b.len() - 1] / lead;
+        q[i] = factor;
+        for (j, coeff) in b.iter().enumerate() {
+            r[i + j] -= factor * coeff;
+        }
+    }
+
+    r.truncate(b.len() - 1);
+    while r.last() == Some(&0.) {
+        r.pop();
+    }
+    (q, r)
+}
+
+fn dense_pow(a: &[f64], exp: usize) -> Vec<f64> {
+    (0..exp).fold(vec![1.], |acc, _| dense_mul(&acc, a))
+}
+
+/// finds `b` such that `b^k == a` by matching coefficients from the leading term down.
+fn dense_root(a: &[f64], k: usize) -> Option<Vec<f64>> {
+    let n = a.len().checked_sub(1)?;
+    if k == 0 || !n.is_multiple_of(k) {
+        return None;
+    }
+    let m = n / k;
+
+    let lead = a[n];
+    let root = match (lead < 0., k.is_multiple_of(2)) {
+        (true, true) => return None,
+        (true, false) => -(-lead).powf(1. / k as f64),
+        (false, _) => lead.powf(1. / k as f64),
+    };
+
+    let mut b = vec![0.; m + 1];
+    b[m] = root;
+
+    // coefficient of x^(n-j) in b^k is k * root^(k-1) * b[m-j] + (terms in b[m-j+1..])
+    let scale = k as f64 * powu(root, k - 1);
+    for j in 1..=m {
+        let current = dense_pow(&b, k)[n - j];
+        b[m - j] = (a[n - j] - current) / scale;
+    }
+
+    let power = dense_pow(&b, k);
+    match power.iter().zip(a).all(|(x, y)| approx_eq(*x, *y)) {
+        true => Some(b),
+        false => None,
+    }
+}
+
+impl Add for Poly {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self.0.extend(rhs.0);
+        self
+    }
+}
+
+impl AddAssign for Poly {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0.extend(rhs.0)
+    }
+}
+
+impl Neg for Poly {
+    type Output = Self;
+
+    fn neg(mut self) -> Self::Output {
+        for term in &mut self.0 {
+            term.coeff *= -1.;
+        }
+        self
+    }
+}
+
+impl Sub for Poly {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self.0.extend(rhs.neg().0);
+        self
+    }
+}
+
+impl SubAssign for Poly {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0.extend(rhs.neg().0);
+    }
+}
+
+impl AddAssign<&Poly> for Poly {
+    fn add_assign(&mut self, rhs: &Poly) {
+        self.0.extend(rhs.0.iter().cloned())
+    }
+}
+
+impl SubAssign<&Poly> for Poly {
+    fn sub_assign(&mut self, rhs: &Poly) {
+        self.0.extend(rhs.0.iter().map(|term| PolyTerm {
+            coeff: -term.coeff,
+            vars: term.vars.clone(),
+        }));
+    }
+}
+
+impl Mul<&Poly> for &Poly {
+    type Output = Poly;
+
+    fn mul(self, rhs: &Poly) -> Self::Output {
+        let mut res = Poly(vec![]);
+        for term1 in &self.0 {
+            for term2 in &rhs.0 {
+                let term = PolyTerm {
+                    coeff: term1.coeff * term2.coeff,
+                    vars: {
+                        let mut vars = vec![];
+                        vars.extend(term1.vars.clone());
+                        vars.extend(term2.vars.clone());
+                        vars
+                    },
+                };
+                res.0.push(term);
+            }
+        }
+        res
+    }
+}
+
+impl Add<PolyTerm> for Poly {
+    type Output = Self;
+
+    fn add(mut self, rhs: PolyTerm) -> Self::Output {
+        self.0.push(rhs);
+        self
+    }
+}
+
+impl Sub<PolyTerm> for Poly {
+    type Output = Self;
+
+    fn sub(mut self, mut rhs: PolyTerm) -> Self::Output {
+        rhs.coeff *= -1.;
+        self.0.push(rhs);
+        self
+    }
+}
+
+impl Sum for Poly {
+    fn sum<I: Iterator<Item = Poly>>(iter: I) -> Self {
+        let mut res = iter.fold(Poly::zero(), |acc, p| acc + p);
+        res.simplify();
+        res
+    }
+}
+
+/// Indexes the stored terms like `get`, but panics out of bounds like `Vec`.
+impl Index<usize> for Poly {
+    type Output = PolyTerm;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for Poly {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl FromIterator<PolyTerm> for Poly {
+    fn from_iter<I: IntoIterator<Item = PolyTerm>>(iter: I) -> Self {
+        Poly::new(iter.into_iter().collect())
+    }
+}
+
+impl Product for Poly {
+    fn product<I: Iterator<Item = Poly>>(iter: I) -> Self {
+        iter.fold(Poly::one(), |acc, p| {
+            let mut res = &acc * &p;
+            res.simplify();
+            res
+        })
+    }
+}
+
+impl Mul<&PolyTerm> for &Poly {
+    type Output = Poly;
+
+    fn mul(self, rhs: &PolyTerm) -> Self::Output {
+        Poly(self.0.iter().map(|term| term * rhs).collect())
+    }
+}
+
+impl Mul for PolyTerm {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul<&PolyTerm> for &PolyTerm {
+    type Output = PolyTerm;
+
+    fn mul(self, rhs: &PolyTerm) -> Self::Output {
+        let mut term = PolyTerm {
+            coeff: self.coeff * rhs.coeff,
+            vars: self.vars.iter().chain(&rhs.vars).cloned().collect(),
+        };
+        term.normalize_vars();
+        term
+    }
+}
+
+impl Poly {
+    /// Renders like `Display` but with every coeff formatted by `fmt`.
+    /// A coeff of exactly `1` on a non constant term is still omitted, and
+    /// the `+` between terms is still added for positive coeffs.
+    ///
+    /// 0.5x2 + 1e20 with |c| format!("{:.2}", c) => 0.50x2 +100000000000000000000.00
+    pub fn to_string_with(&self, fmt: impl Fn(f64) -> String) -> String {
+        self.render(&fmt, &|var| var.to_string())
+    }
+
+    /// Renders like `Display` but with coeffs that are (within floating point
+    /// noise) a fraction with denominator up to 1000 printed as that reduced
+    /// fraction. Other coeffs fall back to decimal.
+    ///
+    /// 0.3333333333333333x2 - 0.5x + 0.1234567 => 1/3x2 -1/2x +0.1234567
+    pub fn to_fraction_string(&self) -> String {
+        self.to_string_with(
+            |coeff| match RatCoeff::approximate(coeff, MAX_FRACTION_DEN) {
+                Some(r) => r.to_string(),
+                None => coeff.to_string(),
+            },
+        )
+    }
+
+    /// Renders like `Display` but with unicode superscript exponents.
+    ///
+    /// 3x2y10 + (yy)2 => 3x²y¹⁰ +(yy)²
+    pub fn to_unicode(&self) -> String {
+        self.render(&|coeff| coeff.to_string(), &|var| {
+            let mut s = var.sym_string();
+            if var.deg != 1 {
+                s.extend(var.deg.to_string().chars().map(superscript));
+            }
+            s
+        })
+    }
+
+    /// The simplified polynomial as a Graphviz DOT expression tree: a `+`
+    /// root with one `*` node per term, whose children are the coeff and one
+    /// `sym^deg` node per var.
+    ///
+    /// 3x2y - 1 =>
+    /// digraph poly {
+    ///     sum [label="+"];
+    ///     t0 [label="*"];
+    ///     sum -> t0;
+    ///     t0c [label="3"];
+    ///     t0 -> t0c;
+    ///     t0v0 [label="x^2"];
+    ///     t0 -> t0v0;
+    ///     ...
+    /// }
+    pub fn to_dot(&self) -> String {
+        // DOT strings only need quotes and backslashes escaped
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut s = String::from("digraph poly {\n    sum [label=\"+\"];\n");
+        for (i, term) in self.canonical().0.iter().enumerate() {
+            s += &format!("    t{} [label=\"*\"];\n    sum -> t{};\n", i, i);
+            s += &format!(
+                "    t{}c [label=\"{}\"];\n    t{} -> t{}c;\n",
+                i, term.coeff, i, i
+            );
+            for (j, var) in term.vars.iter().enumerate() {
+                let label = match var.deg {
+                    1 => escape(&var.sym),
+                    deg => format!("{}^{}", escape(&var.sym), deg),
+                };
+                s += &format!(
+                    "    t{}v{} [label=\"{}\"];\n    t{} -> t{}v{};\n",
+                    i, j, label, i, i, j
+                );
+            }
+        }
+        s += "}\n";
+        s
+    }
+
+    /// Renders like `Display` but for tables of polynomials: every term,
+    /// the first one included, gets its sign and is right padded to `width`
+    /// chars before the separating space, so the terms of polynomials printed
+    /// on consecutive lines start in the same columns. Longer terms are not
+    /// cut, they push the rest of their line over. Trailing padding is trimmed.
+    ///
+    /// 3x2 - x + 1 with width 6 => `+3x2   -1x    +1`
+    /// y - 10 with width 6      => `+y     -10`
+    pub fn to_aligned_string(&self, width: usize) -> String {
+        let terms = self
+            .0
+            .iter()
+            .map(|term| {
+                let sign = match term.coeff < 0. {
+                    true => "",
+                    false => "+",
+                };
+                format!("{}{}", sign, term)
+            })
+            .map(|term| format!("{:<width$}", term, width = width))
+            .collect::<Vec<_>>();
+        terms.join(" ").trim_end().to_string()
+    }
+
+    /// Renders the collected form in `main` (see `as_poly_in`): each power of
+    /// `main` followed by its coeff in parentheses.
+    ///
+    /// x2y + x2 + 3xy + 4 with main x => x2 (y +1) +x (3y) +4
+    pub fn to_grouped_string(&self, main: &str) -> String {
+        let mut s = String::new();
+        for (i, (deg, coeff)) in self.as_poly_in(main).into_iter().enumerate() {
+            let power = PolyVar {
+                sym: main.to_string(),
+                deg,
+            };
+            let group = match (deg, coeff.0.as_slice()) {
+                (0, _) => coeff.to_string(),
+                (_, [term]) if term.vars.is_empty() => PolyTerm {
+                    coeff: term.coeff,
+                    vars: vec![power],
+                }
+                .to_string(),
+                _ => format!("{} ({})", power, coeff),
+            };
+
+            if i > 0 {
+                s += " ";
+                if !group.starts_with('-') {
+                    s += "+";
+                }
+            }
+            s += &group;
+        }
+        s
+    }
+
+    /// the layout shared by `Display` and the other string conversions.
+    /// terms are separated by a space and positive ones get a `+`.
+    fn render(&self, coeff: &impl Fn(f64) -> String, var: &impl Fn(&PolyVar) -> String) -> String {
+        let mut s = String::new();
+        if let [first, rest @ ..] = self.0.as_slice() {
+            s += &first.render(coeff, var);
+
+            for term in rest {
+                s += " ";
+                if term.coeff > 0. {
+                    s += "+";
+                }
+                s += &term.render(coeff, var);
+            }
+        }
+        s
+    }
+}
+
+impl PolyTerm {
+    fn render(&self, coeff: &impl Fn(f64) -> String, var: &impl Fn(&PolyVar) -> String) -> String {
+        // x0 == 1, so zero degree vars are not printed at all
+        // 4x0y2 => 4y2
+        let vars = self.vars.iter().filter(|v| v.deg != 0).collect::<Vec<_>>();
+
+        let mut s = String::new();
+        if self.coeff != 1. || vars.is_empty() {
+            s += &coeff(self.coeff);
+        }
+        for v in vars {
+            s += &var(v);
+        }
+        s
+    }
+}
+
+impl PolyVar {
+    /// the symbol, in parentheses unless it is a single letter.
+    fn sym_string(&self) -> String {
+        // anything longer (x1, yy) or not a letter (a digit) is ambiguous next
+        // to other symbols and exponents: x12 could be x1 squared or x^12.
+        // chars, not bytes, so α is a single letter too
+        let mut chars = self.sym.chars();
+        let single_letter =
+            matches!((chars.next(), chars.next()), (Some(c), None) if c.is_alphabetic());
+        match single_letter {
+            true => self.sym.clone(),
+            false => format!("({})", self.sym),
+        }
+    }
+}
+
+fn superscript(digit: char) -> char {
+    match digit {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        c => c,
+    }
+}
+
+impl Display for Poly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.render(&|coeff| coeff.to_string(), &|var| var.to_string());
+        write!(f, "{}", s)
+    }
+}
+
+impl Display for PolyTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.render(&|coeff| coeff.to_string(), &|var| var.to_string());
+        write!(f, "{}", s)
+    }
+}
+
+impl Display for PolyVar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.deg == 0 {
+            return Ok(());
+        }
+        write!(f, "{}", self.sym_string())?;
+        if self.deg != 1 {
+            write!(f, "{}", self.deg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Asserts that two polynomials are equal once simplified, so term order
+/// and unmerged like terms don't matter. On failure the message lists every
+/// monomial whose coeffs differ.
+///
+/// assert_poly_eq!(x + 1 + x, 2x + 1)
+#[macro_export]
+macro_rules! assert_poly_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_poly_approx_eq!($left, $right, 0.)
+    };
+}
+
+/// Like `assert_poly_eq!`, but coeffs only need to be within `eps` of each
+/// other (see `Poly::approx_eq`).
+///
+/// assert_poly_approx_eq!(0.1x + 0.2x, 0.3x, 1e-12)
+#[macro_export]
+macro_rules! assert_poly_approx_eq {
+    ($left:expr, $right:expr, $eps:expr $(,)?) => {{
+        let (left, right): (&$crate::Poly, &$crate::Poly) = (&$left, &$right);
+        let diff = left.monomial_diff(right, $eps);
+        if !diff.is_empty() {
+            let mut msg = format!(
+                "assertion `left == right` failed for polynomials\n  left: {}\n right: {}\n",
+                left.canonical(),
+                right.canonical(),
+            );
+            for (vars, a, b) in diff {
+                let monomial = $crate::PolyTerm { coeff: 1., vars };
+                msg += &format!("  {}: {} != {} (by {})\n", monomial, a, b, a - b);
+            }
+            panic!("{}", msg);
+        }
+    }};
+}
+
+#[cfg(feature = "rand")]
+impl Poly {
+    /// A random simplified polynomial for fuzzing, built from `num_terms`
+    /// random terms (fewer once like terms merge or cancel).
+    ///
+    /// - each coeff is a uniform non zero integer in `-10..=10`, so sums and
+    ///   products of random polynomials stay exact in `f64`
+    /// - each term has total degree at most `max_degree`: `vars` are visited
+    ///   in a random order and each gets an exponent uniform in what is left
+    ///   of the degree budget
+    pub fn random(
+        vars: &[&str],
+        max_degree: usize,
+        num_terms: usize,
+        rng: &mut impl rand::Rng,
+    ) -> Poly {
+        use rand::seq::SliceRandom;
+
+        let mut vars = vars.to_vec();
+        let terms = (0..num_terms)
+            .map(|_| {
+                let coeff = match rng.gen_range(1..=10) {
+                    c if rng.gen_bool(0.5) => -c,
+                    c => c,
+                };
+
+                vars.shuffle(rng);
+                let mut budget = max_degree;
+                let vars = vars
+                    .iter()
+                    .map(|sym| {
+                        let deg = rng.gen_range(0..=budget);
+                        budget -= deg;
+                        PolyVar {
+                            sym: sym.to_string(),
+                            deg,
+                        }
+                    })
+                    .collect();
+
+                PolyTerm {
+                    coeff: coeff as f64,
+                    vars,
+                }
+            })
+            .collect();
+
+        Poly::new(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(terms: &[(f64, Vec<(&str, usize)>)]) -> Poly {
+        Poly(
+            terms
+                .iter()
+                .map(|(coeff, vars)| PolyTerm {
+                    coeff: *coeff,
+                    vars: vars
+                        .iter()
+                        .map(|(sym, deg)| PolyVar {
+                            sym: sym.to_string(),
+                            deg: *deg,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn substitute() {
+        let mut p = Poly(vec![
+            PolyTerm {
+                coeff: 1.,
+                vars: vec![PolyVar {
+                    sym: "x".to_string(),
+                    deg: 2,
+                }],
+            },
+            PolyTerm {
+                coeff: -2.,
+                vars: vec![
+                    PolyVar {
+                        sym: "x".to_string(),
+                        deg: 3,
+                    },
+                    PolyVar {
+                        sym: "yy".to_string(),
+                        deg: 1,
+                    },
+                    PolyVar {
+                        sym: "z".to_string(),
+                        deg: 2,
+                    },
+                ],
+            },
+            PolyTerm {
+                coeff: 10.,
+                vars: vec![PolyVar {
+                    sym: "y".to_string(),
+                    deg: 2,
+                }],
+            },
+        ]);
+
+        println!("{}", p);
+        p.substitute("x", 2.);
+        println!("{}", p);
+        p.simplify();
+        println!("{}", p);
+
+        let p2 = Poly(vec![]);
+
+        p += p2;
+        // let p3 = p + p2;
+    }
+
+    #[test]
+    fn display_constant_one() {
+        let p = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(p.to_string(), "x2 +1");
+        assert_eq!(poly(&[(1., vec![])]).to_string(), "1");
+        assert_eq!(poly(&[(-1., vec![])]).to_string(), "-1");
+    }
+
+    #[test]
+    fn chaining() {
+        let mut p = Poly(vec![PolyTerm {
+            coeff: 3.,
+            vars: vec![
+                PolyVar {
+                    sym: "x".to_string(),
+                    deg: 2,
+                },
+                PolyVar {
+                    sym: "y".to_string(),
+                    deg: 1,
+                },
+            ],
+        }]);
+
+        p.simplify()
+            .differentiate("x")
+            .substitute("y", 2.)
+            .simplify();
+
+        assert_eq!(
+            p,
+            Poly(vec![PolyTerm {
+                coeff: 12.,
+                vars: vec![PolyVar {
+                    sym: "x".to_string(),
+                    deg: 1,
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn as_perfect_power() {
+        let x_plus_1 = poly(&[(1., vec![("x", 1)]), (1., vec![])]);
+        let mut square = &x_plus_1 * &x_plus_1;
+        square.simplify();
+        assert_eq!(square.as_perfect_power("x"), Some((x_plus_1.clone(), 2)));
+
+        let x_minus_2 = poly(&[(1., vec![("x", 1)]), (-2., vec![])]);
+        let mut cube = &(&x_minus_2 * &x_minus_2) * &x_minus_2;
+        cube.simplify();
+        assert_eq!(cube.as_perfect_power("x"), Some((x_minus_2, 3)));
+
+        let mut fourth = &square * &square;
+        fourth.simplify();
+        assert_eq!(fourth.as_perfect_power("x"), Some((x_plus_1, 4)));
+
+        let not_power = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(not_power.as_perfect_power("x"), None);
+
+        let multivariate = poly(&[(1., vec![("x", 2)]), (1., vec![("y", 2)])]);
+        assert_eq!(multivariate.as_perfect_power("x"), None);
+    }
+
+    #[test]
+    fn weighted_degree() {
+        let p = poly(&[(1., vec![("x", 2)]), (1., vec![("y", 1)])]);
+        let weights = HashMap::from([("y".to_string(), 2)]);
+
+        assert_eq!(p.degree(), 2);
+        assert_eq!(p.weighted_degree(&weights), 2);
+        assert!(p.is_weighted_homogeneous(&weights));
+        assert!(!p.is_weighted_homogeneous(&HashMap::new()));
+
+        let q = poly(&[(1., vec![("x", 1), ("y", 1)]), (1., vec![("x", 1)])]);
+        assert_eq!(q.weighted_degree(&weights), 3);
+        assert!(!q.is_weighted_homogeneous(&weights));
+    }
+
+    #[test]
+    fn new_is_canonical() {
+        let p = Poly::new(vec![
+            PolyTerm {
+                coeff: 2.,
+                vars: vec![
+                    PolyVar {
+                        sym: "y".to_string(),
+                        deg: 1,
+                    },
+                    PolyVar {
+                        sym: "x".to_string(),
+                        deg: 0,
+                    },
+                ],
+            },
+            PolyTerm {
+                coeff: 0.,
+                vars: vec![],
+            },
+        ]);
+        assert_eq!(p, poly(&[(2., vec![("y", 1)])]));
+
+        let a = poly(&[
+            (1., vec![("y", 2)]),
+            (1., vec![("x", 1), ("y", 1)]),
+            (1., vec![("x", 2)]),
+        ]);
+        let b = poly(&[
+            (1., vec![("x", 2)]),
+            (1., vec![("y", 1), ("x", 1)]),
+            (1., vec![("y", 2)]),
+        ]);
+        assert_ne!(a, b);
+        assert_eq!(a.canonical(), b.canonical());
+        assert_eq!(
+            a.canonical(),
+            poly(&[
+                (1., vec![("x", 2)]),
+                (1., vec![("x", 1), ("y", 1)]),
+                (1., vec![("y", 2)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn eval_grid() {
+        let p = poly(&[(1., vec![("x", 2)]), (-1., vec![])]);
+        assert_eq!(
+            p.eval_grid("x", -1., 1., 5),
+            Some(vec![
+                (-1., 0.),
+                (-0.5, -0.75),
+                (0., -1.),
+                (0.5, -0.75),
+                (1., 0.)
+            ])
+        );
+        assert_eq!(p.eval_grid("x", 0., 1., 0), Some(vec![]));
+        assert_eq!(p.eval_grid("x", 3., 4., 1), Some(vec![(3., 8.)]));
+
+        let q = poly(&[(1., vec![("x", 1), ("y", 1)])]);
+        assert_eq!(q.eval_grid("x", 0., 1., 3), None);
+    }
+
+    #[test]
+    fn div_rem() {
+        // x3 - 2x2 - 4 = (x - 3)(x2 + x + 3) + 5
+        let p = poly(&[(1., vec![("x", 3)]), (-2., vec![("x", 2)]), (-4., vec![])]);
+        let d = poly(&[(1., vec![("x", 1)]), (-3., vec![])]);
+        let (q, r) = p.div_rem(&d, "x").unwrap();
+        assert_eq!(
+            q,
+            poly(&[(1., vec![("x", 2)]), (1., vec![("x", 1)]), (3., vec![])])
+        );
+        assert_eq!(r, poly(&[(5., vec![])]));
+
+        assert_eq!(p.div_rem(&Poly(vec![]), "x"), None);
+    }
+
+    #[test]
+    fn eval_multipoint() {
+        // small lcg so the test is deterministic
+        let mut seed = 42u64;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.
+        };
+
+        let coeffs = (0..20).map(|_| rand()).collect::<Vec<_>>();
+        let points = (0..17).map(|_| rand()).collect::<Vec<_>>();
+        let p = Poly::from_coeffs("x", &coeffs);
+
+        let fast = p.eval_multipoint("x", &points).unwrap();
+        assert_eq!(fast.len(), points.len());
+        for (x, y) in points.iter().zip(fast) {
+            assert!((horner(&coeffs, *x) - y).abs() < 1e-9);
+        }
+
+        assert_eq!(p.eval_multipoint("x", &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn power_sums() {
+        // (x - 1)(x - 2)(x - 3)
+        let p = poly(&[
+            (1., vec![("x", 3)]),
+            (-6., vec![("x", 2)]),
+            (11., vec![("x", 1)]),
+            (-6., vec![]),
+        ]);
+        assert_eq!(p.power_sums("x", 4), Some(vec![6., 14., 36., 98.]));
+        assert_eq!((-p.clone()).power_sums("x", 2), Some(vec![6., 14.]));
+        assert_eq!(Poly::from_power_sums("x", &[6., 14., 36.]), p);
+
+        assert_eq!(Poly(vec![]).power_sums("x", 2), None);
+    }
+
+    #[test]
+    fn set_var_order() {
+        let mut p = poly(&[
+            (3., vec![("x", 1), ("t", 1)]),
+            (1., vec![("y", 1)]),
+            (1., vec![("t", 2)]),
+            (1., vec![("z", 1), ("x", 1)]),
+        ]);
+
+        p.set_var_order(&["t"]);
+        assert_eq!(p.to_string(), "t2 +3tx +xz +y");
+
+        p.set_var_order(&["z", "t"]);
+        assert_eq!(p.to_string(), "zx +t2 +3tx +y");
+    }
+
+    #[test]
+    fn reduce_mod_cyclic() {
+        let mut p = &poly(&[(1., vec![("x", 4)])]) * &poly(&[(1., vec![("x", 1)])]);
+        p.reduce_mod_cyclic("x", 5);
+        assert_eq!(p, poly(&[(1., vec![])]));
+
+        let mut q = poly(&[(1., vec![("x", 4), ("y", 1)]), (2., vec![("x", 2)])]);
+        q.reduce_mod_cyclic("x", 3);
+        assert_eq!(
+            q,
+            poly(&[(2., vec![("x", 2)]), (1., vec![("x", 1), ("y", 1)])])
+        );
+
+        let modulus = poly(&[(1., vec![("x", 5)]), (-1., vec![])]);
+        let r = poly(&[(1., vec![("x", 6)]), (1., vec![("x", 2)])]);
+        assert_eq!(
+            r.reduce_mod_poly(&modulus, "x"),
+            Some(poly(&[(1., vec![("x", 2)]), (1., vec![("x", 1)])]))
+        );
+    }
+
+    #[test]
+    fn term_arithmetic() {
+        let t = |coeff: f64, vars: Vec<(&str, usize)>| poly(&[(coeff, vars)]).0.remove(0);
+
+        let a = t(2., vec![("y", 1), ("x", 1)]);
+        let b = t(3., vec![("x", 1), ("z", 1), ("x", 1), ("y", 1)]);
+        assert_eq!(
+            a.clone() * b.clone(),
+            t(6., vec![("x", 3), ("y", 2), ("z", 1)])
+        );
+
+        assert!(a.divides(&b));
+        assert!(!b.divides(&a));
+        assert!(!t(0., vec![]).divides(&a));
+
+        assert_eq!(b.divide(&a), Some(t(1.5, vec![("x", 1), ("z", 1)])));
+        assert_eq!(a.divide(&b), None);
+        assert_eq!(a.divide(&a), Some(t(1., vec![])));
+    }
+
+    #[test]
+    fn as_poly_in() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (1., vec![("x", 2)]),
+            (3., vec![("x", 1), ("y", 1)]),
+            (4., vec![]),
+        ]);
+
+        let collected = p.as_poly_in("x");
+        assert_eq!(
+            collected,
+            vec![
+                (2, poly(&[(1., vec![("y", 1)]), (1., vec![])])),
+                (1, poly(&[(3., vec![("y", 1)])])),
+                (0, poly(&[(4., vec![])])),
+            ]
+        );
+        assert_eq!(Poly::from_poly_coeffs("x", &collected), p.canonical());
+    }
+
+    #[test]
+    fn differentiate_zero_degree() {
+        let mut p = poly(&[(3., vec![("x", 0), ("y", 1)]), (2., vec![("x", 0)])]);
+        p.differentiate("x");
+        assert_eq!(p, Poly(vec![]));
+
+        let mut q = poly(&[(3., vec![("x", 0), ("x", 2)])]);
+        q.differentiate("x");
+        assert_eq!(q, poly(&[(6., vec![("x", 1)])]));
+    }
+
+    #[test]
+    fn monomial_map() {
+        let a = poly(&[
+            (2., vec![("y", 1), ("x", 1)]),
+            (1., vec![("x", 2)]),
+            (1., vec![]),
+            (1., vec![("x", 2)]),
+        ]);
+        let b = poly(&[
+            (1., vec![]),
+            (2., vec![("x", 2)]),
+            (2., vec![("x", 1), ("y", 1)]),
+        ]);
+        assert_eq!(a.monomial_map(), b.monomial_map());
+
+        let map = a.monomial_map();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&vec![]], 1.);
+    }
+
+    #[test]
+    fn from_coeffs_trims_zeros() {
+        let p = Poly::from_coeffs("x", &[1., 0., 0.]);
+        assert_eq!(p, poly(&[(1., vec![])]));
+        assert_eq!(p.degree(), 0);
+        assert_eq!(p.leading_coeff(), 1.);
+        assert_eq!(p.to_coeffs("x"), Some(vec![1.]));
+
+        let q = Poly::from_coeffs("x", &[0., 2., 0., -3., 0.]);
+        assert_eq!(q.degree(), 3);
+        assert_eq!(q.leading_coeff(), -3.);
+        assert_eq!(q.to_coeffs("x"), Some(vec![0., 2., 0., -3.]));
+
+        let zero = Poly::from_coeffs("x", &[0., 0.]);
+        assert_eq!(zero, Poly(vec![]));
+        assert_eq!(zero.to_coeffs("x"), Some(vec![]));
+        assert_eq!(zero.leading_coeff(), 0.);
+    }
+
+    #[test]
+    fn solve_linear() {
+        // 2xy + 4y2 - 6y = 0 => x = -2y + 3
+        let p = poly(&[
+            (2., vec![("x", 1), ("y", 1)]),
+            (4., vec![("y", 2)]),
+            (-6., vec![("y", 1)]),
+        ]);
+        assert_eq!(
+            p.solve_linear("x"),
+            Some(poly(&[(-2., vec![("y", 1)]), (3., vec![])]))
+        );
+
+        let q = poly(&[(2., vec![("x", 1)])]);
+        assert_eq!(q.solve_linear("x"), Some(Poly(vec![])));
+
+        // x2 + 1 is not linear in x
+        let r = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(r.solve_linear("x"), None);
+
+        // xy + 1 has no polynomial solution for x
+        let s = poly(&[(1., vec![("x", 1), ("y", 1)]), (1., vec![])]);
+        assert_eq!(s.solve_linear("x"), None);
+    }
+
+    #[test]
+    fn linear_system_matrix() {
+        let polys = [
+            poly(&[(1., vec![("x", 1)]), (2., vec![("y", 1)]), (-3., vec![])]),
+            poly(&[(4., vec![("y", 1)]), (1., vec![])]),
+        ];
+        assert_eq!(
+            super::linear_system_matrix(&polys, &["x", "y"]),
+            Ok((vec![vec![1., 2.], vec![0., 4.]], vec![3., -1.]))
+        );
+
+        let nonlinear = [polys[0].clone(), poly(&[(1., vec![("x", 1), ("y", 1)])])];
+        assert_eq!(
+            super::linear_system_matrix(&nonlinear, &["x", "y"]),
+            Err(SymbaError::NotLinear { index: 1 })
+        );
+        assert_eq!(
+            super::linear_system_matrix(&polys, &["x"]),
+            Err(SymbaError::NotLinear { index: 0 })
+        );
+    }
+
+    #[test]
+    fn binomial_expand() {
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let one = poly(&[(1., vec![])]);
+
+        let mut row = vec![1.];
+        for n in 0..12 {
+            let p = Poly::binomial_expand(&x, &one, n);
+            assert_eq!(p.to_coeffs("x").unwrap(), row);
+            row = (0..=n + 1)
+                .map(|k| match k {
+                    0 => 1.,
+                    k if k == n + 1 => 1.,
+                    k => row[k - 1] + row[k],
+                })
+                .collect();
+        }
+
+        let y = poly(&[(1., vec![("y", 1)])]);
+        let z = poly(&[(1., vec![("z", 1)])]);
+        let mut expected = one.clone();
+        let sum = x.clone() + y.clone() + z.clone();
+        for _ in 0..4 {
+            expected = &expected * &sum;
+        }
+        expected.simplify();
+        assert_eq!(Poly::multinomial(&["x", "y", "z"], 4), expected);
+        assert_eq!(
+            Poly::binomial_expand(&x, &y, 4),
+            Poly::multinomial(&["x", "y"], 4)
+        );
+    }
+
+    #[test]
+    fn to_unicode() {
+        let p = poly(&[
+            (3., vec![("x", 2)]),
+            (-1., vec![("x", 10), ("y", 1)]),
+            (2., vec![("yy", 3)]),
+            (1., vec![]),
+        ]);
+        assert_eq!(p.to_unicode(), "3x² -1x¹⁰y +2(yy)³ +1");
+        assert_eq!(p.to_string(), "3x2 -1x10y +2(yy)3 +1");
+        assert_eq!(Poly(vec![]).to_unicode(), "");
+    }
+
+    #[test]
+    fn norms() {
+        // x and -4x merge into -3x before measuring
+        let p = poly(&[(1., vec![("x", 1)]), (-4., vec![("x", 1)]), (4., vec![])]);
+        assert_eq!(p.max_norm(), 4.);
+        assert_eq!(p.l1_norm(), 7.);
+        assert_eq!(p.l2_norm(), 5.);
+
+        assert_eq!(Poly(vec![]).max_norm(), 0.);
+        assert_eq!(Poly(vec![]).l2_norm(), 0.);
+    }
+
+    #[test]
+    fn eval_matrix() {
+        // x2 - 5x + 6 at [[1, 2], [3, 4]]
+        // A2 = [[7, 10], [15, 22]], so p(A) = [[7 - 5 + 6, 10 - 10], [15 - 15, 22 - 20 + 6]]
+        let p = poly(&[(1., vec![("x", 2)]), (-5., vec![("x", 1)]), (6., vec![])]);
+        let a = vec![vec![1., 2.], vec![3., 4.]];
+        assert_eq!(p.eval_matrix("x", &a), Ok(vec![vec![8., 0.], vec![0., 8.]]));
+
+        // cayley hamilton: the characteristic polynomial x2 - 5x - 2 annihilates A
+        let charpoly = poly(&[(1., vec![("x", 2)]), (-5., vec![("x", 1)]), (-2., vec![])]);
+        assert_eq!(
+            charpoly.eval_matrix("x", &a),
+            Ok(vec![vec![0., 0.], vec![0., 0.]])
+        );
+
+        assert_eq!(
+            p.eval_matrix("x", &[vec![1., 2.]]),
+            Err(SymbaError::NotSquare)
+        );
+    }
+
+    #[test]
+    fn coeff_poly_of() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (1., vec![("x", 1), ("y", 1)]),
+            (1., vec![("y", 2)]),
+        ]);
+        assert_eq!(
+            p.coeff_poly_of("y", 1),
+            poly(&[(1., vec![("x", 2)]), (1., vec![("x", 1)])])
+        );
+        assert_eq!(p.coeff_poly_of("y", 2), poly(&[(1., vec![])]));
+        assert_eq!(p.coeff_poly_of("y", 0), Poly(vec![]));
+    }
+
+    #[test]
+    fn simplify_negative_zero() {
+        let x2 = poly(&[(1., vec![("x", 2)])]);
+        let mut p = x2.clone() - x2.clone();
+        p.simplify();
+        assert_eq!(p, Poly(vec![]));
+        assert_eq!(p.to_string(), "");
+
+        let mut q = poly(&[(1., vec![("y", 1)])]) + x2.clone() - x2;
+        q.simplify();
+        assert_eq!(q.to_string(), "y");
+
+        let mut r = -poly(&[(0., vec![("x", 1)]), (2., vec![])]);
+        assert_eq!(r.0[0].coeff.to_string(), "-0");
+        r.simplify();
+        assert_eq!(r.to_string(), "-2");
+    }
+
+    #[test]
+    fn pow() {
+        let p = poly(&[(1., vec![("x", 1)]), (1., vec![("y", 1)])]);
+        assert_eq!(p.pow(5), Poly::multinomial(&["x", "y"], 5));
+        assert_eq!(p.pow(0), poly(&[(1., vec![])]));
+        assert_eq!(p.checked_pow(2), Ok(p.pow(2)));
+        assert_eq!(p.checked_pow(-1), Err(SymbaError::NegativeExponent));
+
+        let c = poly(&[(2., vec![])]);
+        assert_eq!(c.checked_pow(-2), Ok(poly(&[(0.25, vec![])])));
+        assert_eq!(
+            Poly(vec![]).checked_pow(-1),
+            Err(SymbaError::DivisionByZero)
+        );
+        assert_eq!(Poly(vec![]).checked_pow(0), Ok(poly(&[(1., vec![])])));
+    }
+
+    #[test]
+    fn to_grouped_string() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (3., vec![("x", 1), ("y", 1)]),
+            (1., vec![("x", 2)]),
+            (4., vec![]),
+        ]);
+        assert_eq!(p.to_grouped_string("x"), "x2 (y +1) +x (3y) +4");
+        assert_eq!(p.to_grouped_string("y"), "y (x2 +3x) +x2 +4");
+
+        let q = poly(&[(-2., vec![("x", 3)]), (1., vec![("x", 1)])]);
+        assert_eq!(q.to_grouped_string("x"), "-2x3 +x");
+    }
+
+    #[test]
+    fn substitute_ratio() {
+        let mut p = poly(&[(1., vec![("x", 2)])]);
+        p.substitute_ratio("x", 1, 3);
+        assert_eq!(p, poly(&[(1. / 9., vec![])]));
+
+        let mut q = poly(&[(9., vec![("x", 3), ("y", 1)]), (1., vec![])]);
+        q.substitute_ratio("x", -2, 3);
+        assert_eq!(q, poly(&[(-8. / 3., vec![("y", 1)]), (1., vec![])]));
+
+        // an integer coeff is multiplied in exactly: 3^35 doesn't fit an f64
+        let mut r = poly(&[(3., vec![("x", 1)])]);
+        r.substitute_ratio("x", 3i64.pow(35), 1);
+        assert_eq!(r, poly(&[(3i128.pow(36) as f64, vec![])]));
+
+        // 2^53 + 1 is odd, but rounds to an even f64
+        let mut minus_one = poly(&[(1., vec![("x", (1 << 53) + 1)])]);
+        minus_one.substitute_ratio("x", -1, 1);
+        assert_eq!(minus_one.to_string(), "-1");
+    }
+
+    #[test]
+    fn display_zero_degree() {
+        let p = poly(&[
+            (4., vec![("x", 0), ("y", 2)]),
+            (1., vec![("x", 0)]),
+            (-1., vec![("z", 0), ("x", 1)]),
+        ]);
+        assert_eq!(p.to_string(), "4y2 +1 -1x");
+        assert_eq!(p.to_unicode(), "4y² +1 -1x");
+        assert_eq!(p.0[1].vars[0].to_string(), "");
+    }
+
+    #[test]
+    fn substitute_all() {
+        let mut p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (-2., vec![("z", 1)]),
+            (3., vec![("x", 1)]),
+        ]);
+        let bindings = HashMap::from([("x".to_string(), 2.), ("y".to_string(), 3.)]);
+
+        assert_eq!(p.evaluate(&bindings), None);
+        p.substitute_all(&bindings);
+        assert_eq!(p, poly(&[(-2., vec![("z", 1)]), (18., vec![])]));
+
+        let all = HashMap::from([("z".to_string(), 4.)]);
+        assert_eq!(p.evaluate(&all), Some(10.));
+        assert_eq!(Poly(vec![]).evaluate(&all), Some(0.));
+    }
+
+    #[test]
+    fn mul_capped() {
+        let p = Poly::multinomial(&["x", "y", "z", "w"], 1);
+        assert_eq!(p.mul_capped(&p, 16), Ok(p.pow(2)));
+        assert_eq!(
+            p.mul_capped(&p, 15),
+            Err(SymbaError::TooManyTerms { terms: 16, max: 15 })
+        );
+
+        assert_eq!(p.pow_capped(3, 100), Ok(p.pow(3)));
+        assert!(matches!(
+            p.pow_capped(1000, 1000),
+            Err(SymbaError::TooManyTerms { .. })
+        ));
+    }
+
+    #[test]
+    fn add_assign_ref() {
+        let addends = [
+            poly(&[(1., vec![("x", 1)])]),
+            poly(&[(2., vec![("x", 1)]), (1., vec![])]),
+        ];
+
+        let mut sum = Poly(vec![]);
+        for p in &addends {
+            sum += p;
+        }
+        sum -= &addends[1];
+        sum.simplify();
+
+        assert_eq!(sum, addends[0]);
+    }
+
+    #[test]
+    fn vieta() {
+        // 2(x - 1)(x - 2)(x + 3) = 2x3 - 14x + 12
+        let p = poly(&[(2., vec![("x", 3)]), (-14., vec![("x", 1)]), (12., vec![])]);
+        assert_eq!(p.roots_sum("x"), Ok(0.));
+        assert_eq!(p.roots_product("x"), Ok(-6.));
+
+        // x2 - 5x + 6
+        let q = poly(&[(1., vec![("x", 2)]), (-5., vec![("x", 1)]), (6., vec![])]);
+        assert_eq!(q.roots_sum("x"), Ok(5.));
+        assert_eq!(q.roots_product("x"), Ok(6.));
+
+        assert_eq!(
+            poly(&[(3., vec![])]).roots_sum("x"),
+            Err(SymbaError::ConstantPolynomial)
+        );
+        assert_eq!(
+            Poly(vec![]).roots_product("x"),
+            Err(SymbaError::ConstantPolynomial)
+        );
+    }
+
+    #[test]
+    fn push_term() {
+        let mut p = Poly(vec![]);
+        for _ in 0..3 {
+            p.push_term(PolyTerm {
+                coeff: 2.,
+                vars: vec![PolyVar {
+                    sym: "x".to_string(),
+                    deg: 1,
+                }],
+            });
+        }
+        assert_eq!(p.0.len(), 3);
+        assert_eq!(p.simplify(), &poly(&[(6., vec![("x", 1)])]));
+
+        p.clear();
+        assert_eq!(p, Poly(vec![]));
+    }
+
+    #[test]
+    fn exact_gcd() {
+        let a = poly(&[(6., vec![("x", 2)]), (-6., vec![])]);
+        let b = poly(&[(4., vec![("x", 1)]), (-4., vec![])]);
+        assert_eq!(
+            a.gcd(&b, "x"),
+            Some(poly(&[(1., vec![("x", 1)]), (-1., vec![])]))
+        );
+
+        // (x - 1/3)(x + 2) and (x - 1/3)(x - 5), read from their (inexact) f64 coeffs
+        let c = poly(&[(3., vec![("x", 2)]), (5., vec![("x", 1)]), (-2., vec![])]);
+        let d = poly(&[(3., vec![("x", 2)]), (-16., vec![("x", 1)]), (5., vec![])]);
+        let g = c.gcd(&d, "x").unwrap();
+        assert_eq!(g.to_coeffs("x"), Some(vec![-1. / 3., 1.]));
+
+        let (q, r) = a.div_rem_exact(&b, "x").unwrap();
+        assert_eq!(q, poly(&[(1.5, vec![("x", 1)]), (1.5, vec![])]));
+        assert_eq!(r, Poly(vec![]));
+
+        let e = poly(&[(0.5, vec![("x", 1)]), (0.25, vec![])]);
+        assert_eq!(e.content(), Some(RatCoeff::new(1, 4)));
+        assert_eq!(
+            e.primitive_part(),
+            Some(poly(&[(2., vec![("x", 1)]), (1., vec![])]))
+        );
+
+        let f = poly(&[(-6., vec![("x", 2)]), (9., vec![("x", 1)]), (3., vec![])]);
+        assert_eq!(f.content(), Some(RatCoeff::from_integer(-3)));
+        assert_eq!(
+            f.primitive_part(),
+            Some(poly(&[
+                (2., vec![("x", 2)]),
+                (-3., vec![("x", 1)]),
+                (-1., vec![])
+            ]))
+        );
+    }
+
+    #[test]
+    fn terms_ordered() {
+        let a = poly(&[
+            (1., vec![("y", 2)]),
+            (2., vec![("x", 1)]),
+            (3., vec![]),
+            (4., vec![("x", 1), ("z", 2)]),
+            (5., vec![("y", 1), ("x", 2)]),
+        ]);
+        let b = poly(&[
+            (5., vec![("x", 2), ("y", 1)]),
+            (3., vec![]),
+            (4., vec![("z", 2), ("x", 1)]),
+            (2., vec![("x", 1)]),
+            (1., vec![("y", 2)]),
+        ]);
+
+        let coeffs = |p: &Poly, order| {
+            p.terms_ordered(order)
+                .iter()
+                .map(|term| term.coeff)
+                .collect::<Vec<_>>()
+        };
+        for order in [
+            MonomialOrder::Lex,
+            MonomialOrder::GrLex,
+            MonomialOrder::GRevLex,
+        ] {
+            assert_eq!(coeffs(&a, order), coeffs(&b, order));
+        }
+
+        assert_eq!(coeffs(&a, MonomialOrder::Lex), [5., 4., 2., 1., 3.]);
+        assert_eq!(coeffs(&a, MonomialOrder::GrLex), [5., 4., 1., 2., 3.]);
+        assert_eq!(coeffs(&a, MonomialOrder::GRevLex), [5., 4., 1., 2., 3.]);
+
+        let x2z = &poly(&[(1., vec![("x", 2), ("z", 1)])]).0[0];
+        let xy2 = &poly(&[(1., vec![("x", 1), ("y", 2)])]).0[0];
+        assert_eq!(MonomialOrder::GrLex.compare(x2z, xy2), Ordering::Greater);
+        assert_eq!(MonomialOrder::GRevLex.compare(x2z, xy2), Ordering::Less);
+
+        assert_eq!(
+            a.canonical().0,
+            a.terms_ordered(MonomialOrder::GrLex)
+                .into_iter()
+                .cloned()
+                .map(|mut term| {
+                    term.normalize_vars();
+                    term
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn factors_over_rationals() {
+        let p = poly(&[(1., vec![("x", 2)]), (-5., vec![("x", 1)]), (6., vec![])]);
+        assert_eq!(p.factors_over_rationals("x"), Some(true));
+        let q = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(q.factors_over_rationals("x"), Some(false));
+        let q = poly(&[(1., vec![("x", 2)]), (-2., vec![])]);
+        assert_eq!(q.factors_over_rationals("x"), Some(false));
+
+        // 4x2 - 1/4 = (2x - 1/2)(2x + 1/2)
+        let q = poly(&[(4., vec![("x", 2)]), (-0.25, vec![])]);
+        assert_eq!(q.factors_over_rationals("x"), Some(true));
+
+        // 2x3 - x2 - 2x + 1 = (2x - 1)(x - 1)(x + 1)
+        let r = poly(&[
+            (2., vec![("x", 3)]),
+            (-1., vec![("x", 2)]),
+            (-2., vec![("x", 1)]),
+            (1., vec![]),
+        ]);
+        assert_eq!(r.factors_over_rationals("x"), Some(true));
+        assert_eq!(
+            r.rational_roots("x"),
+            Some(vec![
+                RatCoeff::from_integer(-1),
+                RatCoeff::new(1, 2),
+                RatCoeff::ONE
+            ])
+        );
+        assert_eq!(
+            r.rational_root_candidates("x"),
+            Some(vec![
+                RatCoeff::from_integer(-1),
+                RatCoeff::new(-1, 2),
+                RatCoeff::new(1, 2),
+                RatCoeff::ONE,
+            ])
+        );
+
+        // x3 - 2 has no rational root, so it is irreducible
+        let s = poly(&[(1., vec![("x", 3)]), (-2., vec![])]);
+        assert_eq!(s.factors_over_rationals("x"), Some(false));
+        let t = poly(&[(1., vec![("x", 3)]), (1., vec![("y", 1)])]);
+        assert_eq!(t.factors_over_rationals("x"), None);
+    }
+
+    #[test]
+    fn to_string_with() {
+        let p = poly(&[(0.5, vec![("x", 2)]), (1., vec![("x", 1)]), (-1e20, vec![])]);
+        assert_eq!(
+            p.to_string_with(|c| format!("{:.2}", c)),
+            "0.50x2 +x -100000000000000000000.00"
+        );
+        assert_eq!(p.to_string_with(|c| format!("{:e}", c)), "5e-1x2 +x -1e20");
+    }
+
+    #[test]
+    fn wronskian() {
+        let one = poly(&[(1., vec![])]);
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let x2 = poly(&[(1., vec![("x", 2)])]);
+        assert_eq!(
+            super::wronskian(&[one.clone(), x.clone(), x2.clone()], "x"),
+            poly(&[(2., vec![])])
+        );
+
+        // x and x2 => x * 2x - x2 * 1 = x2
+        assert_eq!(super::wronskian(&[x.clone(), x2.clone()], "x"), x2);
+
+        // 2x + 3 depends on 1 and x
+        let dependent = poly(&[(2., vec![("x", 1)]), (3., vec![])]);
+        assert_eq!(super::wronskian(&[one, x, dependent], "x"), Poly(vec![]));
+    }
+
+    #[test]
+    fn poly_det() {
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let y = poly(&[(1., vec![("y", 1)])]);
+        let c = |v: f64| poly(&[(v, vec![])]);
+
+        // | x  y |
+        // | y  x | = x2 - y2
+        assert_eq!(
+            super::poly_det(&[vec![x.clone(), y.clone()], vec![y.clone(), x.clone()]]),
+            Ok(poly(&[(1., vec![("x", 2)]), (-1., vec![("y", 2)])]))
+        );
+
+        // | x  1  0 |
+        // | 1  x  1 |
+        // | 0  1  x | = x3 - 2x
+        let tridiagonal = vec![
+            vec![x.clone(), c(1.), c(0.)],
+            vec![c(1.), x.clone(), c(1.)],
+            vec![c(0.), c(1.), x.clone()],
+        ];
+        assert_eq!(
+            super::poly_det(&tridiagonal),
+            Ok(poly(&[(1., vec![("x", 3)]), (-2., vec![("x", 1)])]))
+        );
+
+        // bareiss (with a zero pivot) agrees with cofactor expansion
+        let big = vec![
+            vec![c(0.), x.clone(), y.clone(), c(1.), c(2.)],
+            vec![x.clone(), c(1.), c(0.), y.clone(), c(3.)],
+            vec![c(2.), y.clone(), x.clone(), c(0.), c(1.)],
+            vec![c(1.), c(0.), c(4.), x.clone(), y.clone()],
+            vec![y.clone(), c(1.), c(1.), c(1.), x.clone()],
+        ];
+        assert_eq!(
+            bareiss_det(big.clone()).map(|d| d.monomial_map()),
+            Some(cofactor_det(&big).monomial_map())
+        );
+
+        assert_eq!(
+            super::poly_det(&[vec![x.clone(), y]]),
+            Err(SymbaError::NotSquare)
+        );
+        assert_eq!(super::poly_det(&[]), Ok(c(1.)));
+    }
+
+    #[test]
+    fn truncate_in() {
+        let mut p = Poly::from_coeffs("x", &[1., 1., 1., 1.]);
+        p.truncate_in("x", 2);
+        assert_eq!(p, Poly::from_coeffs("x", &[1., 1., 1.]));
+
+        let mut q = poly(&[
+            (1., vec![("e", 3), ("x", 1)]),
+            (2., vec![("e", 1), ("x", 4)]),
+            (1., vec![("e", 1), ("e", 1)]),
+        ]);
+        q.truncate_in("e", 2);
+        assert_eq!(
+            q,
+            poly(&[(2., vec![("e", 1), ("x", 4)]), (1., vec![("e", 2)])])
+        );
+    }
+
+    #[test]
+    fn to_integral() {
+        let p = poly(&[(0.5, vec![("x", 1)]), (1., vec![])]);
+        assert!(!p.is_integral(1e-6));
+        assert_eq!(p.to_integral(1e-6), None);
+
+        let q = poly(&[(2.0000001, vec![("x", 1)]), (3., vec![])]);
+        assert!(q.is_integral(1e-6));
+        assert!(!q.is_integral(0.));
+        assert_eq!(
+            q.to_integral(1e-6),
+            Some(poly(&[(2., vec![("x", 1)]), (3., vec![])]))
+        );
+    }
+
+    #[test]
+    fn reverse_coeffs() {
+        let p = Poly::from_coeffs("x", &[5., 3., 2.]);
+        assert_eq!(
+            p.reverse_coeffs("x"),
+            Some(Poly::from_coeffs("x", &[2., 3., 5.]))
+        );
+
+        let palindromic = Poly::from_coeffs("x", &[1., 4., 1.]);
+        assert_eq!(palindromic.reverse_coeffs("x"), Some(palindromic));
+
+        // x3 + x2 => x + 1, the zero root has no reciprocal
+        let q = Poly::from_coeffs("x", &[0., 0., 1., 1.]);
+        assert_eq!(
+            q.reverse_coeffs("x"),
+            Some(Poly::from_coeffs("x", &[1., 1.]))
+        );
+
+        assert_eq!(poly(&[(1., vec![("y", 1)])]).reverse_coeffs("x"), None);
+    }
+
+    #[test]
+    fn degree_opt() {
+        assert_eq!(poly(&[(5., vec![])]).degree_opt(), Some(0));
+        assert_eq!(Poly(vec![]).degree_opt(), None);
+        assert_eq!(poly(&[(0., vec![("x", 3)])]).degree_opt(), None);
+        assert_eq!(poly(&[(0., vec![("x", 3)])]).degree(), 3);
+        assert_eq!(
+            poly(&[(1., vec![("x", 1), ("y", 2)]), (1., vec![])]).degree_opt(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn poly_term_ops() {
+        let t = poly(&[(2., vec![("y", 1)])]).0.remove(0);
+        let p = poly(&[(1., vec![("x", 1)]), (1., vec![])]);
+
+        let mut sum = p.clone() + t.clone();
+        sum.simplify();
+        assert_eq!(sum.to_string(), "x +2y +1");
+
+        let mut diff = p.clone() - t.clone();
+        diff.simplify();
+        assert_eq!(diff.to_string(), "x -2y +1");
+
+        let mut prod = &p * &t;
+        prod.simplify();
+        assert_eq!(prod.to_string(), "2xy +2y");
+    }
+
+    #[test]
+    fn is_canonical() {
+        let p = poly(&[
+            (1., vec![("x", 2)]),
+            (3., vec![("x", 1), ("y", 1)]),
+            (1., vec![]),
+        ]);
+        assert!(p.is_canonical());
+        assert!(Poly(vec![]).is_canonical());
+
+        let unmerged = poly(&[(1., vec![("x", 1)]), (2., vec![("x", 1)])]);
+        assert!(!unmerged.is_canonical());
+        assert!(unmerged.canonical().is_canonical());
+
+        assert!(!poly(&[(0., vec![("x", 1)])]).is_canonical());
+        assert!(!poly(&[(1., vec![("x", 0)])]).is_canonical());
+        assert!(!poly(&[(1., vec![("y", 1), ("x", 1)])]).is_canonical());
+        assert!(!poly(&[(1., vec![("x", 1), ("x", 1)])]).is_canonical());
+        assert!(!poly(&[(1., vec![]), (1., vec![("x", 1)])]).is_canonical());
+    }
+
+    #[test]
+    fn eval_interval() {
+        let contains = |(lo, hi): (f64, f64), p: &Poly, a: f64, b: f64| {
+            p.eval_grid("x", a, b, 101)
+                .unwrap()
+                .into_iter()
+                .all(|(_, y)| lo <= y && y <= hi)
+        };
+
+        // monotone on [0, 2]: x3 + x ranges over [0, 10]
+        let p = Poly::from_coeffs("x", &[0., 1., 0., 1.]);
+        let enclosure = p.eval_interval("x", 0., 2.).unwrap();
+        assert!(enclosure.0 <= 0. && enclosure.1 >= 10.);
+        assert!(enclosure.0 > -1e-9 && enclosure.1 < 10. + 1e-9);
+        assert!(contains(enclosure, &p, 0., 2.));
+
+        // x2 - 1 on [-1, 2] dips to -1 at 0
+        let q = Poly::from_coeffs("x", &[-1., 0., 1.]);
+        let enclosure = q.eval_interval("x", -1., 2.).unwrap();
+        assert!(enclosure.0 <= -1. && enclosure.1 >= 3.);
+        assert!(contains(enclosure, &q, -1., 2.));
+
+        // powers too big for f64 to hold exactly are still enclosed: 3^80 and -3^79
+        for (k, sign) in [(80, 1.), (79, -1.)] {
+            let xk = poly(&[(1., vec![("x", k)])]);
+            let (lo, hi) = xk.eval_interval("x", 3. * sign, 3. * sign).unwrap();
+            let (lo, hi) = match sign > 0. {
+                true => (lo, hi),
+                false => (-hi, -lo),
+            };
+            assert!(lo < hi);
+            assert!(lo as u128 <= 3u128.pow(k as u32) && 3u128.pow(k as u32) <= hi as u128);
+        }
+
+        assert_eq!(
+            poly(&[(1., vec![("y", 1)])]).eval_interval("x", 0., 1.),
+            None
+        );
+    }
+
+    #[test]
+    fn exponent_vectors() {
+        let p = poly(&[
+            (3., vec![("z", 1), ("x", 2)]),
+            (1., vec![("y", 1)]),
+            (5., vec![]),
+        ]);
+        assert_eq!(
+            p.exponent_vectors(&["x", "y", "z"]),
+            Some(vec![
+                (vec![2, 0, 1], 3.),
+                (vec![0, 1, 0], 1.),
+                (vec![0, 0, 0], 5.)
+            ])
+        );
+        assert_eq!(
+            p.exponent_vectors(&["z", "y", "x", "w"]),
+            Some(vec![
+                (vec![1, 0, 2, 0], 3.),
+                (vec![0, 1, 0, 0], 1.),
+                (vec![0, 0, 0, 0], 5.)
+            ])
+        );
+        assert_eq!(p.exponent_vectors(&["x", "y"]), None);
+    }
+
+    #[test]
+    fn sum_and_product() {
+        let polys = vec![
+            poly(&[(1., vec![("x", 1)]), (1., vec![])]),
+            poly(&[(1., vec![("x", 1)]), (-1., vec![])]),
+            poly(&[(2., vec![("x", 1)])]),
+        ];
+
+        assert_eq!(
+            polys.clone().into_iter().sum::<Poly>(),
+            poly(&[(4., vec![("x", 1)])])
+        );
+        assert_eq!(
+            polys.into_iter().product::<Poly>(),
+            poly(&[(2., vec![("x", 3)]), (-2., vec![("x", 1)])])
+        );
+
+        assert_eq!(Vec::<Poly>::new().into_iter().sum::<Poly>(), Poly::zero());
+        assert_eq!(
+            Vec::<Poly>::new().into_iter().product::<Poly>(),
+            Poly::one()
+        );
+    }
+
+    #[test]
+    fn reduce_fraction() {
+        // (x2 - 1) / (x + 1) => x - 1
+        let num = Poly::from_coeffs("x", &[-1., 0., 1.]);
+        let den = Poly::from_coeffs("x", &[1., 1.]);
+        assert_eq!(
+            super::reduce_fraction(&num, &den, "x"),
+            Some((Poly::from_coeffs("x", &[-1., 1.]), Poly::one()))
+        );
+
+        // (2x2 - 2) / (-4x2 + 4x) => (-x - 1) / (2x)
+        let den = Poly::from_coeffs("x", &[0., 4., -4.]);
+        let num = Poly::from_coeffs("x", &[-2., 0., 2.]);
+        assert_eq!(
+            super::reduce_fraction(&num, &den, "x"),
+            Some((
+                Poly::from_coeffs("x", &[-2., -2.]),
+                Poly::from_coeffs("x", &[0., 4.])
+            ))
+        );
+
+        assert_eq!(super::reduce_fraction(&num, &Poly::zero(), "x"), None);
+    }
+
+    #[test]
+    fn to_fraction_string() {
+        let p = poly(&[
+            (1. / 3., vec![("x", 2)]),
+            (-0.5, vec![("x", 1)]),
+            (0.1234567, vec![]),
+        ]);
+        assert_eq!(p.to_fraction_string(), "1/3x2 -1/2x +0.1234567");
+        assert_eq!(
+            poly(&[(2., vec![("x", 1)]), (-1. / 7., vec![])]).to_fraction_string(),
+            "2x -1/7"
+        );
+    }
+
+    #[test]
+    fn normalize_vars() {
+        let mut term = PolyTerm {
+            coeff: 4.,
+            vars: vec![
+                PolyVar {
+                    sym: "y".into(),
+                    deg: 2,
+                },
+                PolyVar {
+                    sym: "x".into(),
+                    deg: 0,
+                },
+                PolyVar {
+                    sym: "x".into(),
+                    deg: 3,
+                },
+                PolyVar {
+                    sym: "y".into(),
+                    deg: 1,
+                },
+            ],
+        };
+        term.normalize_vars();
+        assert_eq!(
+            term,
+            PolyTerm {
+                coeff: 4.,
+                vars: vec![
+                    PolyVar {
+                        sym: "x".into(),
+                        deg: 3
+                    },
+                    PolyVar {
+                        sym: "y".into(),
+                        deg: 3
+                    },
+                ],
+            }
+        );
+        assert_eq!(term.to_string(), "4x3y3");
+    }
+
+    #[test]
+    fn integrate_with_constant() {
+        let p = poly(&[(3., vec![("x", 2)]), (1., vec![("x", 1), ("y", 1)])]);
+        let mut integral = p.integrate_with_constant("x", "C");
+        assert_eq!(integral.to_string(), "x3 +0.5x2y +C");
+
+        integral.differentiate("x");
+        assert_eq!(integral, p.canonical());
+    }
+
+    #[test]
+    fn eval_multivariate_horner() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (3., vec![("x", 1), ("y", 1)]),
+            (1., vec![("x", 1)]),
+        ]);
+        let bindings = HashMap::from([("x".to_string(), 2.), ("y".to_string(), 3.)]);
+        assert_eq!(p.eval_multivariate_horner(&bindings), 32.);
+
+        let p = poly(&[
+            (2.5, vec![("x", 5), ("z", 2)]),
+            (-1., vec![("y", 3), ("z", 1)]),
+            (0.5, vec![("x", 1), ("y", 1), ("z", 4)]),
+            (7., vec![("x", 2)]),
+            (-4., vec![]),
+        ]);
+        for (x, y, z) in [(0.5, -1.5, 2.), (1.1, 0.3, -0.7), (-2., 3., 0.)] {
+            let bindings = HashMap::from([
+                ("x".to_string(), x),
+                ("y".to_string(), y),
+                ("z".to_string(), z),
+            ]);
+            let expected = p.evaluate(&bindings).unwrap();
+            assert!((p.eval_multivariate_horner(&bindings) - expected).abs() < 1e-9);
+        }
+
+        assert_eq!(Poly::zero().eval_multivariate_horner(&HashMap::new()), 0.);
+        assert!(p.eval_multivariate_horner(&HashMap::new()).is_nan());
+    }
+
+    #[test]
+    fn parity() {
+        use super::Parity;
+
+        let even = poly(&[(1., vec![("x", 4)]), (1., vec![("x", 2)]), (1., vec![])]);
+        let odd = poly(&[(1., vec![("x", 3)]), (1., vec![("x", 1)])]);
+        let neither = poly(&[(1., vec![("x", 2)]), (1., vec![("x", 1)])]);
+        assert_eq!(even.parity("x"), Parity::Even);
+        assert_eq!(odd.parity("x"), Parity::Odd);
+        assert_eq!(neither.parity("x"), Parity::Neither);
+
+        // x3 - x3 + x2 cancels down to even
+        let cancels = poly(&[
+            (1., vec![("x", 3)]),
+            (-1., vec![("x", 3)]),
+            (1., vec![("x", 2)]),
+        ]);
+        assert_eq!(cancels.parity("x"), Parity::Even);
+        assert_eq!(odd.parity("y"), Parity::Even);
+        assert_eq!(Poly::zero().parity("x"), Parity::Even);
+    }
+
+    #[test]
+    fn into_terms_from_iter() {
+        let p = poly(&[
+            (1., vec![("x", 1)]),
+            (2., vec![("x", 2)]),
+            (3., vec![("x", 1)]),
+        ]);
+        let terms = p.clone().into_terms();
+        assert_eq!(terms, p.canonical().0);
+        assert_eq!(terms.len(), 2);
+
+        let doubled: Poly = terms
+            .into_iter()
+            .map(|mut term| {
+                term.coeff *= 2.;
+                term
+            })
+            .collect();
+        assert_eq!(doubled.to_string(), "4x2 +8x");
+    }
+
+    #[test]
+    fn degree_histogram() {
+        let p = poly(&[
+            (1., vec![("x", 3)]),
+            (1., vec![("x", 2), ("y", 1)]),
+            (1., vec![("x", 1), ("y", 1)]),
+            (4., vec![]),
+            (-1., vec![("x", 1), ("y", 1)]),
+        ]);
+        assert_eq!(p.degree_histogram(), BTreeMap::from([(0, 1), (3, 2)]));
+        assert!(Poly::zero().degree_histogram().is_empty());
+    }
+
+    #[test]
+    fn pow_mod() {
+        let x = poly(&[(1., vec![("x", 1)])]);
+        // x2 + 2 is irreducible mod 5, so this is GF(25) where frobenius
+        // x -> x^5 has order 2
+        let modulus = poly(&[(1., vec![("x", 2)]), (2., vec![])]);
+        assert_eq!(x.pow_mod(25, &modulus, 5), Some(x.clone()));
+        assert_eq!(
+            x.pow_mod(5, &modulus, 5),
+            Some(poly(&[(4., vec![("x", 1)])]))
+        );
+
+        // fermat: 3^4 == 1 mod 5, and x + 1 has order dividing 24 in GF(25)*
+        let three = poly(&[(3., vec![])]);
+        assert_eq!(three.pow_mod(4, &modulus, 5), Some(Poly::one()));
+        let x1 = poly(&[(1., vec![("x", 1)]), (1., vec![])]);
+        assert_eq!(x1.pow_mod(24, &modulus, 5), Some(Poly::one()));
+        assert_eq!(x1.pow_mod(0, &modulus, 5), Some(Poly::one()));
+
+        // 1/2 is 3 mod 5
+        let half = poly(&[(0.5, vec![])]);
+        assert_eq!(half.pow_mod(1, &modulus, 5), Some(three));
+
+        let y = poly(&[(1., vec![("y", 1)])]);
+        assert_eq!(y.pow_mod(2, &modulus, 5), None);
+        assert_eq!(x.pow_mod(2, &poly(&[(5., vec![("x", 1)])]), 5), None);
+        assert_eq!(x.pow_mod(2, &modulus, 1), None);
+
+        // coeffs near p above 2^63 don't overflow: (x - 1)^5 = -8x2 - 5x + 19 mod x3 - 2
+        let p = (1 << 63) + 29;
+        let x_1 = poly(&[(1., vec![("x", 1)]), (-1., vec![])]);
+        let cubic = poly(&[(1., vec![("x", 3)]), (-2., vec![])]);
+        assert_eq!(
+            x_1.pow_mod(5, &cubic, p),
+            Some(poly(&[
+                ((p - 8) as f64, vec![("x", 2)]),
+                ((p - 5) as f64, vec![("x", 1)]),
+                (19., vec![]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn substitute_fn() {
+        let mut p = poly(&[
+            (2., vec![("c1", 1), ("x", 1)]),
+            (1., vec![("c2", 1)]),
+            (1., vec![("x", 1)]),
+        ]);
+        p.substitute_fn(|sym| sym.starts_with('c').then_some(0.));
+        assert_eq!(p.to_string(), "x");
+
+        let mut p = poly(&[(1., vec![("x", 2), ("y", 1)]), (3., vec![("y", 2)])]);
+        p.substitute_fn(|sym| match sym {
+            "y" => Some(2.),
+            _ => None,
+        });
+        assert_eq!(p.to_string(), "2x2 +12");
+    }
+
+    #[test]
+    fn substitute_exact_integer_powers() {
+        let mut p = poly(&[(1., vec![("x", 10)])]);
+        p.substitute("x", 2.);
+        assert_eq!(p.canonical().to_string(), "1024");
+
+        // powi drifts here: 10f64.powi(33) != 1e33
+        let p = poly(&[(1., vec![("x", 33)]), (1., vec![("y", 20)])]);
+        let bindings = HashMap::from([("x".to_string(), 10.), ("y".to_string(), 0.)]);
+        assert_eq!(p.evaluate(&bindings), Some(1e33));
+
+        // too big for i128 or not an integer, so powi it is
+        let p = poly(&[(1., vec![("x", 40)])]);
+        let bindings = HashMap::from([("x".to_string(), 10.)]);
+        assert_eq!(p.evaluate(&bindings), Some(10f64.powi(40)));
+        let bindings = HashMap::from([("x".to_string(), 0.5)]);
+        assert_eq!(p.evaluate(&bindings), Some(0.5f64.powi(40)));
+    }
+
+    #[test]
+    fn to_dot() {
+        let p = poly(&[(3., vec![("x", 2), ("y", 1)]), (-1., vec![])]);
+        assert_eq!(
+            p.to_dot(),
+            "digraph poly {
+    sum [label=\"+\"];
+    t0 [label=\"*\"];
+    sum -> t0;
+    t0c [label=\"3\"];
+    t0 -> t0c;
+    t0v0 [label=\"x^2\"];
+    t0 -> t0v0;
+    t0v1 [label=\"y\"];
+    t0 -> t0v1;
+    t1 [label=\"*\"];
+    sum -> t1;
+    t1c [label=\"-1\"];
+    t1 -> t1c;
+}
+"
+        );
+
+        let p = poly(&[(1., vec![("a\"b", 1)])]);
+        assert!(p.to_dot().contains("[label=\"a\\\"b\"]"));
+        assert_eq!(
+            Poly::zero().to_dot(),
+            "digraph poly {\n    sum [label=\"+\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn substitute_huge_degree() {
+        // i32::MAX + 2 wraps to a negative exponent as an i32
+        let deg = i32::MAX as usize + 2;
+        let p = poly(&[(1., vec![("x", deg)])]);
+
+        let mut half = p.clone();
+        half.substitute("x", 0.5);
+        assert_eq!(half.canonical(), Poly::zero());
+
+        let mut minus_one = p.clone();
+        minus_one.substitute("x", -1.);
+        assert_eq!(minus_one.canonical().to_string(), "-1");
+
+        let mut minus_half = p;
+        minus_half.substitute("x", -1.5);
+        assert_eq!(minus_half.canonical().leading_coeff(), f64::NEG_INFINITY);
+
+        // 2^53 + 1 is odd, but rounds to an even f64
+        let mut minus_one = poly(&[(1., vec![("x", (1 << 53) + 1)])]);
+        minus_one.substitute("x", -1.);
+        assert_eq!(minus_one.canonical().to_string(), "-1");
+    }
+
+    #[test]
+    fn summation() {
+        let k = poly(&[(1., vec![("k", 1)])]);
+        assert_eq!(k.summation("k", "n").unwrap().to_string(), "0.5n2 +0.5n");
+
+        let p = poly(&[(6., vec![("k", 2)]), (2., vec![])]);
+        assert_eq!(p.summation("k", "n").unwrap().to_string(), "2n3 +3n2 +3n");
+
+        // sum of k^5 for k = 1..=10 and a parameter a
+        let p = poly(&[(1., vec![("a", 1), ("k", 5)])]);
+        let mut s = p.summation("k", "n").unwrap();
+        s.substitute("n", 10.);
+        assert_eq!(s.canonical().to_string(), "220825a");
+
+        assert_eq!(super::faulhaber(59).map(|c| c.len()), Some(61));
+        assert_eq!(super::faulhaber(60), None);
+        assert_eq!(poly(&[(1., vec![("k", 60)])]).summation("k", "n"), None);
+    }
+
+    #[test]
+    fn substitute_poly() {
+        let mut p = poly(&[(1., vec![("x", 2)]), (1., vec![("y", 1)])]);
+        p.substitute_poly("x", &poly(&[(1., vec![("x", 1)]), (1., vec![])]));
+        assert_eq!(p.to_string(), "x2 +2x +y +1");
+
+        let mut p = poly(&[(1., vec![("x", 2), ("z", 1)]), (3., vec![("x", 1)])]);
+        p.substitute_poly("x", &poly(&[(2., vec![("y", 1)])]));
+        assert_eq!(p.to_string(), "4y2z +6y");
+
+        // x·x, not yet normalized, is x2
+        let mut p = Poly(vec![PolyTerm {
+            coeff: 1.,
+            vars: vec![
+                PolyVar {
+                    sym: "x".into(),
+                    deg: 1,
+                },
+                PolyVar {
+                    sym: "x".into(),
+                    deg: 1,
+                },
+            ],
+        }]);
+        p.substitute_poly("x", &poly(&[(1., vec![("y", 1)])]));
+        assert_eq!(p.to_string(), "y2");
+    }
+
+    #[test]
+    fn finite_difference() {
+        let x2 = poly(&[(1., vec![("x", 2)])]);
+        assert_eq!(x2.finite_difference("x").to_string(), "2x +1");
+        assert_eq!(Poly::one().finite_difference("x"), Poly::zero());
+
+        // the difference of the partial sums S(n) = p(1) + .. + p(n) is p(n + 1)
+        let p = poly(&[(3., vec![("k", 3)]), (-1., vec![("k", 1)]), (2., vec![])]);
+        let mut next = p.clone();
+        next.substitute_poly("k", &poly(&[(1., vec![("n", 1)]), (1., vec![])]));
+        assert_eq!(p.summation("k", "n").unwrap().finite_difference("n"), next);
+
+        // and summing differences telescopes: q(n + 1) - q(1)
+        let q = poly(&[(1., vec![("k", 4)]), (2., vec![("k", 1)])]);
+        let mut telescoped = q.clone();
+        telescoped.substitute_poly("k", &poly(&[(1., vec![("n", 1)]), (1., vec![])]));
+        telescoped -= &poly(&[(3., vec![])]);
+        telescoped.simplify();
+        assert_eq!(
+            q.finite_difference("k").summation("k", "n").unwrap(),
+            telescoped
+        );
+    }
+
+    #[test]
+    fn integrate_definite() {
+        let p = poly(&[(3., vec![("x", 2), ("y", 1)])]);
+        assert_eq!(p.integrate_definite("x", 0., 2.).to_string(), "8y");
+        assert_eq!(p.integrate_definite("x", 2., 2.), Poly::zero());
+    }
+
+    #[test]
+    fn area_between() {
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let x2 = poly(&[(1., vec![("x", 2)])]);
+        let area = x.area_between(&x2, "x", 0., 1.).unwrap();
+        assert!((area - 1. / 6.).abs() < 1e-12);
+
+        // signed: the curves cross at 1, and the two halves cancel out here
+        let area = x.area_between(&x2, "x", 0., 1.5).unwrap();
+        assert!(area.abs() < 1e-12);
+        assert_eq!(x2.area_between(&x, "x", 0., 1.).map(|a| a < 0.), Some(true));
+
+        let y = poly(&[(1., vec![("y", 1)])]);
+        assert_eq!(x.area_between(&y, "x", 0., 1.), None);
+        assert_eq!(x.area_between(&x, "x", 0., 1.), Some(0.));
+    }
+
+    #[test]
+    fn simplify_merges_vars_per_term() {
+        let mut p = poly(&[
+            (1., vec![("x", 2), ("y", 1), ("x", 3)]),
+            (2., vec![]),
+            (3., vec![("y", 2), ("x", 1), ("y", 1)]),
+            (4., vec![("x", 0), ("z", 1)]),
+            (5., vec![("z", 1), ("x", 1), ("z", 2), ("y", 1)]),
+            (6., vec![("y", 1), ("x", 5)]),
+            (7., vec![("x", 1)]),
+            (8., vec![("y", 3), ("x", 1)]),
+        ]);
+        p.simplify();
+
+        // every term keeps exactly its own degrees, only identical monomials merge
+        assert_eq!(p.to_string(), "7x5y +5xyz3 +11xy3 +7x +4z +2");
+
+        let mut p = Poly(
+            (1..=20)
+                .map(|i| PolyTerm {
+                    coeff: 1.,
+                    vars: vec![
+                        PolyVar {
+                            sym: "x".into(),
+                            deg: i,
+                        },
+                        PolyVar {
+                            sym: "y".into(),
+                            deg: 1,
+                        },
+                        PolyVar {
+                            sym: "x".into(),
+                            deg: 1,
+                        },
+                    ],
+                })
+                .collect(),
+        );
+        p.simplify();
+        assert_eq!(p.0.len(), 20);
+        for (term, i) in p.0.iter().zip((1..=20).rev()) {
+            assert_eq!(term.to_string(), format!("x{}y", i + 1));
+        }
+    }
+
+    #[test]
+    fn clear_denominators() {
+        let p = poly(&[(0.5, vec![("x", 1)]), (1. / 3., vec![])]);
+        let (scaled, factor) = p.clear_denominators();
+        assert_eq!(scaled.to_string(), "3x +2");
+        assert_eq!(factor, 6.);
+
+        let p = poly(&[(-0.75, vec![("x", 2), ("y", 1)]), (2. / 7., vec![("y", 1)])]);
+        let (scaled, factor) = p.clear_denominators();
+        assert_eq!(scaled.to_string(), "-21x2y +8y");
+        assert_eq!(factor, 28.);
+
+        let (scaled, factor) = poly(&[(2., vec![("x", 1)])]).clear_denominators();
+        assert_eq!((scaled.to_string(), factor), ("2x".to_string(), 1.));
+        assert_eq!(Poly::zero().clear_denominators(), (Poly::zero(), 1.));
+    }
+
+    #[test]
+    fn exponent_moments() {
+        // binomial(10, 1/2)
+        let p = poly(&[(0.5, vec![]), (0.5, vec![("x", 1)])]).pow(10);
+        assert!((p.mean_exponent("x").unwrap() - 5.).abs() < 1e-12);
+        assert!((p.variance_exponent("x").unwrap() - 2.5).abs() < 1e-12);
+
+        // unnormalized: a fair die
+        let die = Poly::from_coeffs("x", &[0., 1., 1., 1., 1., 1., 1.]);
+        assert_eq!(die.mean_exponent("x"), Some(3.5));
+        assert!((die.variance_exponent("x").unwrap() - 35. / 12.).abs() < 1e-12);
+
+        assert_eq!(Poly::zero().mean_exponent("x"), None);
+        let xy = poly(&[(1., vec![("x", 1), ("y", 1)])]);
+        assert_eq!(xy.variance_exponent("x"), None);
+    }
+
+    #[test]
+    fn index() {
+        let mut p = Poly::new(vec![
+            PolyTerm {
+                coeff: 2.,
+                vars: vec![],
+            },
+            PolyTerm {
+                coeff: 3.,
+                vars: vec![PolyVar {
+                    sym: "x".into(),
+                    deg: 2,
+                }],
+            },
+        ]);
+        assert_eq!(p.len(), 2);
+        assert!(!p.is_empty());
+        assert_eq!(p[0].to_string(), "3x2");
+        assert_eq!(p.get(1).map(|term| term.coeff), Some(2.));
+        assert_eq!(p.get(2), None);
+
+        p[1].coeff = -1.;
+        assert_eq!(p.to_string(), "3x2 -1");
+        assert!(Poly::zero().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let _ = &Poly::zero()[0];
+    }
+
+    #[test]
+    fn integrate_definite_exact() {
+        let x2 = poly(&[(1., vec![("x", 2)])]);
+        assert_eq!(
+            x2.integrate_definite_exact("x", (0, 1), (1, 1)),
+            Some((1, 3))
+        );
+
+        // 3x2 - 2x + 1 from 1/2 to 2 => [x3 - x2 + x] = 6 - 3/8
+        let p = poly(&[(3., vec![("x", 2)]), (-2., vec![("x", 1)]), (1., vec![])]);
+        assert_eq!(
+            p.integrate_definite_exact("x", (1, 2), (2, 1)),
+            Some((45, 8))
+        );
+
+        // swapped bounds flip the sign, negative denominators are fine
+        assert_eq!(
+            p.integrate_definite_exact("x", (2, 1), (-1, -2)),
+            Some((-45, 8))
+        );
+
+        // x7 from 0 to 1/3 => 1/(8 * 3^8)
+        let x7 = poly(&[(1., vec![("x", 7)])]);
+        assert_eq!(
+            x7.integrate_definite_exact("x", (0, 1), (1, 3)),
+            Some((1, 52488))
+        );
+
+        let xy = poly(&[(1., vec![("x", 1), ("y", 1)])]);
+        assert_eq!(xy.integrate_definite_exact("x", (0, 1), (1, 1)), None);
+        assert_eq!(x2.integrate_definite_exact("x", (0, 0), (1, 1)), None);
+        assert_eq!(
+            Poly::zero().integrate_definite_exact("x", (0, 1), (1, 1)),
+            Some((0, 1))
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_integrate_differentiate() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..200 {
+            let p = Poly::random(&["x", "y", "z"], 6, 8, &mut rng);
+            assert!(p.is_canonical());
+            assert!(p.degree() <= 6);
+
+            for sym in ["x", "y", "z"] {
+                let mut q = p.clone();
+                q.integrate(sym).differentiate(sym);
+                assert!((q - p.clone()).max_norm() < 1e-9, "{} wrt {}", p, sym);
+            }
+        }
+    }
+
+    #[test]
+    fn assert_poly_eq_macros() {
+        let a = poly(&[(1., vec![("x", 1)]), (1., vec![]), (1., vec![("x", 1)])]);
+        let b = poly(&[(1., vec![]), (2., vec![("x", 1)])]);
+        assert_poly_eq!(a, b);
+
+        let a = poly(&[(0.1, vec![("x", 1)]), (0.2, vec![("x", 1)])]);
+        let b = poly(&[(0.3, vec![("x", 1)])]);
+        assert!(!a.approx_eq(&b, 0.));
+        assert_poly_approx_eq!(a, b, 1e-12);
+
+        let a = poly(&[(1., vec![("x", 2)]), (2., vec![("x", 1)])]);
+        let b = poly(&[(1., vec![("x", 2)]), (3., vec![])]);
+        let x = vec![PolyVar {
+            sym: "x".into(),
+            deg: 1,
+        }];
+        assert_eq!(a.monomial_diff(&b, 0.), vec![(vec![], 0., 3.), (x, 2., 0.)]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "left: x2 +2x\n right: x2 +3\n  1: 0 != 3 (by -3)\n  x: 2 != 0 (by 2)\n"
+    )]
+    fn assert_poly_eq_diff_message() {
+        let a = poly(&[(1., vec![("x", 2)]), (2., vec![("x", 1)])]);
+        let b = poly(&[(1., vec![("x", 2)]), (3., vec![])]);
+        assert_poly_eq!(a, b);
+    }
+
+    #[test]
+    fn bivariate_grid() {
+        let p = Poly::from_bivariate_grid("x", "y", &[vec![1., 2.], vec![0., 3.]]);
+        assert_eq!(p.to_string(), "3xy +2y +1");
+        assert_eq!(
+            p.to_bivariate_grid("x", "y"),
+            Some(vec![vec![1., 2.], vec![0., 3.]])
+        );
+
+        let grid = vec![vec![0., 0., -1.], vec![4.], vec![0., 0.5, 0.]];
+        let p = Poly::from_bivariate_grid("x", "y", &grid);
+        assert_eq!(p.to_string(), "0.5x2y -1y2 +4x");
+        let back = p.to_bivariate_grid("x", "y").unwrap();
+        assert_eq!(
+            back,
+            vec![vec![0., 0., -1.], vec![4., 0., 0.], vec![0., 0.5, 0.]]
+        );
+        assert_eq!(Poly::from_bivariate_grid("x", "y", &back), p);
+
+        assert_eq!(Poly::zero().to_bivariate_grid("x", "y"), Some(vec![]));
+        let z = poly(&[(1., vec![("z", 1)])]);
+        assert_eq!(z.to_bivariate_grid("x", "y"), None);
+    }
+
+    #[test]
+    fn is_irreducible_q() {
+        let x2_2 = poly(&[(1., vec![("x", 2)]), (-2., vec![])]);
+        let x2_4 = poly(&[(1., vec![("x", 2)]), (-4., vec![])]);
+        assert_eq!(x2_2.is_irreducible_q("x"), Some(true));
+        assert_eq!(x2_4.is_irreducible_q("x"), Some(false));
+
+        // x3 - 2 has no rational root, 2x3 - x2 has the root 1/2 (and 0)
+        let x3_2 = poly(&[(1., vec![("x", 3)]), (-2., vec![])]);
+        assert_eq!(x3_2.is_irreducible_q("x"), Some(true));
+        let p = poly(&[(2., vec![("x", 3)]), (-1., vec![("x", 2)])]);
+        assert_eq!(p.is_irreducible_q("x"), Some(false));
+
+        // eisenstein at 3, and at 2 for the reversal 1 + 2x + 2x2 + 2x3 + 2x4 + 2x5
+        let p = poly(&[(1., vec![("x", 5)]), (6., vec![("x", 2)]), (3., vec![])]);
+        assert_eq!(p.is_irreducible_q("x"), Some(true));
+        let p = Poly::from_coeffs("x", &[2., 2., 2., 2., 2., 1.]);
+        assert_eq!(p.is_irreducible_q("x"), Some(true));
+
+        // reducible with a root, and undecided: x4 + 1 and (x2 + 1)^2
+        let p = poly(&[(1., vec![("x", 4)]), (-1., vec![])]);
+        assert_eq!(p.is_irreducible_q("x"), Some(false));
+        let x4_1 = poly(&[(1., vec![("x", 4)]), (1., vec![])]);
+        assert_eq!(x4_1.is_irreducible_q("x"), None);
+        // too big to factor
+        let p = poly(&[(1., vec![("x", 3)]), (1e30, vec![])]);
+        assert_eq!(p.is_irreducible_q("x"), None);
+        let p = poly(&[(1., vec![("x", 5)]), (1e30, vec![("x", 1)]), (1e30, vec![])]);
+        assert_eq!(p.is_irreducible_q("x"), None);
+        assert_eq!(super::prime_factors(1 << 100), None);
+        assert_eq!(super::prime_factors(-360), Some(vec![2, 3, 5]));
+        assert_eq!(
+            poly(&[(1., vec![("x", 2)]), (1., vec![])])
+                .pow(2)
+                .is_irreducible_q("x"),
+            None
+        );
+
+        assert_eq!(
+            poly(&[(3., vec![("x", 1)])]).is_irreducible_q("x"),
+            Some(true)
+        );
+        assert_eq!(Poly::one().is_irreducible_q("x"), Some(false));
+        assert_eq!(poly(&[(1., vec![("y", 2)])]).is_irreducible_q("x"), None);
+    }
+
+    #[test]
+    fn substitute_polys() {
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let y = poly(&[(1., vec![("y", 1)])]);
+        let p = poly(&[(1., vec![("x", 2), ("y", 1)]), (3., vec![("z", 1)])]);
+
+        let swap = HashMap::from([("x".to_string(), y.clone()), ("y".to_string(), x.clone())]);
+        assert_eq!(p.substitute_polys(&swap).to_string(), "xy2 +3z");
+
+        // one after the other, the second substitution also hits the first's output
+        let mut sequential = p.clone();
+        sequential.substitute_poly("x", &y).substitute_poly("y", &x);
+        assert_eq!(sequential.to_string(), "x3 +3z");
+
+        let bindings = HashMap::from([(
+            "z".to_string(),
+            poly(&[(1., vec![("x", 1)]), (-1., vec![])]),
+        )]);
+        assert_eq!(p.substitute_polys(&bindings).to_string(), "x2y +3x -3");
+        assert_eq!(p.substitute_polys(&HashMap::new()), p.canonical());
+    }
+
+    #[test]
+    fn checked_div_rem() {
+        // x3 - 1 = (x - 1)(x2 + x + 1)
+        let p = poly(&[(1., vec![("x", 3)]), (-1., vec![])]);
+        let d = poly(&[(1., vec![("x", 1)]), (-1., vec![])]);
+        let (q, r) = p.checked_div_rem(&d, "x", 1e-12).unwrap();
+        assert_eq!(q.to_string(), "x2 +x +1");
+        assert_eq!(r, Poly::zero());
+
+        // a tiny leading coeff blows the quotient up to ~1e34, and the
+        // reconstruction loses the small coeffs of the dividend entirely
+        let p = Poly::from_coeffs("x", &[0.1, 0.7, 0.3, 1.9, 0.37, 2.3]);
+        let d = Poly::from_coeffs("x", &[0.3, 0.9, 3e-9]);
+        assert!(p.div_rem(&d, "x").is_some());
+        match p.checked_div_rem(&d, "x", 1e-9) {
+            Err(SymbaError::InexactDivision { residual }) => {
+                assert_eq!(residual.to_coeffs("x").unwrap()[0], -0.1)
+            }
+            res => panic!("expected an inexact division, got {:?}", res),
+        }
+
+        assert_eq!(
+            p.checked_div_rem(&Poly::zero(), "x", 1e-9),
+            Err(SymbaError::DivisionByZero)
+        );
+        assert_eq!(
+            p.checked_div_rem(&poly(&[(1., vec![("y", 1)])]), "x", 1e-9),
+            Err(SymbaError::NotUnivariate { sym: "x".into() })
+        );
+    }
+
+    #[test]
+    fn canonical_key() {
+        let a = poly(&[
+            (3., vec![("x", 2), ("y", 1)]),
+            (1., vec![("y", 1), ("x", 2)]),
+            (-1., vec![]),
+        ]);
+        let b = poly(&[(-1., vec![]), (4., vec![("x", 1), ("y", 1), ("x", 1)])]);
+        assert_eq!(a.canonical_key(), "-1;4|1:x^2|1:y^1");
+        assert_eq!(a.canonical_key(), b.canonical_key());
+        assert_eq!(a.canonical_key(), a.canonical().canonical_key());
+
+        let c = poly(&[(4., vec![("x", 2), ("y", 1)])]);
+        assert_ne!(a.canonical_key(), c.canonical_key());
+        assert_eq!(
+            poly(&[(1., vec![("x", 1)]), (-1., vec![("x", 1)])]).canonical_key(),
+            ""
+        );
+
+        // x * (y^2) vs the single symbol "x|1:y" squared
+        let d = poly(&[(1., vec![("x", 1), ("y", 2)])]);
+        let e = poly(&[(1., vec![("x|1:y", 2)])]);
+        assert_ne!(d.canonical_key(), e.canonical_key());
+    }
+
+    #[test]
+    fn multi_char_symbols() {
+        for (sym, displayed) in [
+            ("alpha", "3(alpha)2 +2(alpha)x +1"),
+            ("x1", "2x(x1) +3(x1)2 +1"),
+            ("yy", "2x(yy) +3(yy)2 +1"),
+            ("α", "2xα +3α2 +1"),
+        ] {
+            // 3s2 + 2sx + 1
+            let p = poly(&[
+                (3., vec![(sym, 2)]),
+                (2., vec![(sym, 1), ("x", 1)]),
+                (1., vec![]),
+            ]);
+            let p = p.canonical();
+            assert_eq!(p.to_string(), displayed);
+            assert_eq!(p.to_string().parse::<Poly>(), Ok(p.clone()), "{}", sym);
+
+            let mut d = p.clone();
+            d.differentiate(sym);
+            assert_eq!(
+                d,
+                poly(&[(6., vec![(sym, 1)]), (2., vec![("x", 1)])]).canonical(),
+                "{}",
+                sym
+            );
+
+            let mut i = d.clone();
+            i.integrate(sym);
+            assert_eq!(
+                i,
+                poly(&[(3., vec![(sym, 2)]), (2., vec![(sym, 1), ("x", 1)])]).canonical()
+            );
+
+            let mut s = p.clone();
+            s.substitute(sym, 2.);
+            assert_eq!(s.canonical().to_string(), "4x +13", "{}", sym);
+            assert_eq!(p.degree(), 2);
+            assert_eq!(p.coeff_poly_of(sym, 1).to_string(), "2x");
+        }
+
+        // a single character that isn't a letter is parenthesized too
+        let p = poly(&[(1., vec![("x", 1), ("1", 2)])]);
+        assert_eq!(p.to_string(), "x(1)2");
+        assert_eq!(p.to_unicode(), "x(1)²");
+    }
+
+    #[test]
+    fn term_pow() {
+        let t = PolyTerm {
+            coeff: 2.,
+            vars: vec![
+                PolyVar {
+                    sym: "y".into(),
+                    deg: 1,
+                },
+                PolyVar {
+                    sym: "x".into(),
+                    deg: 2,
+                },
+            ],
+        };
+        assert_eq!(t.pow(3).to_string(), "8x6y3");
+        assert_eq!(t.pow(0).to_string(), "1");
+        assert_eq!(t.pow(1).to_string(), "2x2y");
+
+        let p = poly(&[(-3., vec![("x", 1), ("z", 2)])]);
+        assert_eq!(p.pow(3).to_string(), "-27x3z6");
+        assert_eq!(p.pow(0), Poly::one());
+    }
+
+    #[test]
+    fn groebner_basis() {
+        use super::{groebner_basis, MonomialOrder};
+
+        let circle = poly(&[(1., vec![("x", 2)]), (1., vec![("y", 2)]), (-1., vec![])]);
+        let line = poly(&[(1., vec![("x", 1)]), (-1., vec![("y", 1)])]);
+
+        let basis = groebner_basis(&[circle.clone(), line.clone()], MonomialOrder::Lex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["x -1y", "y2 -0.5"]);
+
+        // same ideal, but y2 is bigger than x under GrLex
+        let basis = groebner_basis(&[circle, line], MonomialOrder::GrLex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["y2 -0.5", "x -1y"]);
+
+        // {xy - 1, y2 - 1}: the S-polynomial x - y is new, then xy - 1 becomes redundant
+        let f = poly(&[(1., vec![("x", 1), ("y", 1)]), (-1., vec![])]);
+        let g = poly(&[(2., vec![("y", 2)]), (-2., vec![])]);
+        let basis = groebner_basis(&[f, g], MonomialOrder::Lex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["x -1y", "y2 -1"]);
+
+        // the twisted cubic (t, t2, t3): three elements under GRevLex, four
+        // under GrLex where xz beats y2
+        let cubic = [
+            poly(&[(1., vec![("x", 2)]), (-1., vec![("y", 1)])]),
+            poly(&[(1., vec![("x", 3)]), (-1., vec![("z", 1)])]),
+        ];
+        let basis = groebner_basis(&cubic, MonomialOrder::GRevLex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["x2 -1y", "xy -1z", "-1xz +y2"]);
+        let basis = groebner_basis(&cubic, MonomialOrder::GrLex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["y3 -1z2", "x2 -1y", "xy -1z", "xz -1y2"]);
+
+        assert!(groebner_basis(&[Poly::zero()], MonomialOrder::Lex).is_empty());
+        assert_eq!(
+            groebner_basis(&[poly(&[(3., vec![])])], MonomialOrder::Lex),
+            vec![Poly::one()]
+        );
+
+        // a tiny generator is kept, not mistaken for rounding noise
+        let tiny = poly(&[(1e-10, vec![("x", 1)])]);
+        let basis = groebner_basis(&[tiny, poly(&[(1., vec![("y", 1)])])], MonomialOrder::Lex);
+        let basis = basis.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+        assert_eq!(basis, ["x", "y"]);
+    }
+
+    #[test]
+    fn in_ideal() {
+        use super::MonomialOrder;
+
+        let x_1 = poly(&[(1., vec![("x", 1)]), (-1., vec![])]);
+        let x2_1 = poly(&[(1., vec![("x", 2)]), (-1., vec![])]);
+        let x = poly(&[(1., vec![("x", 1)])]);
+        let ideal = [x_1];
+        assert!(x2_1.in_ideal(&ideal, MonomialOrder::Lex));
+        assert!(!x.in_ideal(&ideal, MonomialOrder::Lex));
+        assert!(Poly::zero().in_ideal(&[], MonomialOrder::Lex));
+
+        // y2 - 1 is in (xy - 1, x - y) but only shows up through the S-polynomial:
+        // reducing by the generators as given leaves y2 - 1 untouched
+        let f = poly(&[(1., vec![("x", 1), ("y", 1)]), (-1., vec![])]);
+        let g = poly(&[(1., vec![("x", 1)]), (-1., vec![("y", 1)])]);
+        let y2_1 = poly(&[(1., vec![("y", 2)]), (-1., vec![])]);
+        for order in [
+            MonomialOrder::Lex,
+            MonomialOrder::GrLex,
+            MonomialOrder::GRevLex,
+        ] {
+            assert!(y2_1.in_ideal(&[f.clone(), g.clone()], order));
+            assert!(!poly(&[(1., vec![("y", 1)])]).in_ideal(&[f.clone(), g.clone()], order));
+        }
+
+        // tiny coeffs are not rounding noise unless something cancelled
+        let y = [poly(&[(1., vec![("y", 1)])])];
+        let tiny = poly(&[(1e-10, vec![("x", 1)])]);
+        assert!(!tiny.in_ideal(&y, MonomialOrder::Lex));
+        let tiny_y = poly(&[(1e-10, vec![("x", 1)]), (1., vec![("y", 1)])]);
+        assert!(!tiny_y.in_ideal(&y, MonomialOrder::Lex));
+        // and a tiny generator is as good as any
+        assert!(x2_1.in_ideal(&[&ideal[0] * &poly(&[(1e-12, vec![])])], MonomialOrder::Lex));
+    }
+
+    #[test]
+    fn s_polynomial() {
+        use super::{s_polynomial, MonomialOrder};
+
+        // lcm of x3y2 and 3x4y is x4y2, so S = x f - y/3 g
+        let f = poly(&[
+            (1., vec![("x", 3), ("y", 2)]),
+            (-1., vec![("x", 2), ("y", 3)]),
+            (1., vec![("x", 1)]),
+        ]);
+        let g = poly(&[(3., vec![("x", 4), ("y", 1)]), (1., vec![("y", 2)])]);
+        let s = s_polynomial(&f, &g, MonomialOrder::GrLex);
+        assert_eq!(s.to_fraction_string(), "-1x3y3 -1/3y3 +x2");
+
+        // antisymmetric, and zero against itself or zero
+        let mut sum = s_polynomial(&g, &f, MonomialOrder::GrLex) + s;
+        sum.simplify();
+        assert_eq!(sum, Poly::zero());
+        assert_eq!(s_polynomial(&f, &f, MonomialOrder::GrLex), Poly::zero());
+        assert_eq!(
+            s_polynomial(&f, &Poly::zero(), MonomialOrder::GrLex),
+            Poly::zero()
+        );
+    }
+
+    #[test]
+    fn monomial_lcm_gcd() {
+        let term = |coeff: f64, vars: Vec<(&str, usize)>| poly(&[(coeff, vars)]).0.remove(0);
+
+        let a = term(1., vec![("x", 2), ("y", 1)]);
+        let b = term(1., vec![("x", 1), ("y", 3)]);
+        assert_eq!(a.monomial_lcm(&b).to_string(), "x2y3");
+        assert_eq!(a.monomial_gcd(&b).to_string(), "xy");
+
+        // symbols in one term only, unnormalized vars and integer coeffs
+        let a = term(4., vec![("y", 1), ("x", 1), ("x", 1)]);
+        let b = term(-6., vec![("z", 1), ("y", 3), ("x", 1)]);
+        assert_eq!(a.monomial_lcm(&b).to_string(), "12x2y3z");
+        assert_eq!(a.monomial_gcd(&b).to_string(), "2xy");
+
+        // non integer coeffs give 1, and coprime monomials a constant gcd
+        let a = term(0.5, vec![("x", 1)]);
+        let b = term(3., vec![("y", 1)]);
+        assert_eq!(a.monomial_lcm(&b).to_string(), "xy");
+        assert_eq!(a.monomial_gcd(&b).to_string(), "1");
+
+        // an lcm overflowing i128 gives 1 too
+        let a = term(1e30, vec![("x", 1)]);
+        let b = term(3f64.powi(60), vec![("y", 1)]);
+        assert_eq!(a.monomial_lcm(&b).to_string(), "xy");
+    }
+
+    #[test]
+    fn derivatives_up_to() {
+        let x3 = poly(&[(1., vec![("x", 3)])]);
+        let derivatives = x3.derivatives_up_to("x", 4);
+        let derivatives = derivatives
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(derivatives, ["x3", "3x2", "6x", "6", ""]);
+
+        let p = poly(&[(2., vec![("x", 2), ("y", 1)]), (1., vec![("y", 1)])]);
+        assert_eq!(p.derivatives_up_to("x", 0), vec![p.canonical()]);
+        assert_eq!(p.derivatives_up_to("y", 2)[1].to_string(), "2x2 +1");
+    }
+
+    #[test]
+    fn support() {
+        let p = poly(&[(3., vec![("x", 2)]), (0., vec![("y", 1)]), (5., vec![])]);
+        let support = p.support();
+        assert_eq!(
+            support,
+            vec![
+                PolyTerm {
+                    coeff: 1.,
+                    vars: vec![PolyVar {
+                        sym: "x".into(),
+                        deg: 2
+                    }]
+                },
+                PolyTerm {
+                    coeff: 1.,
+                    vars: vec![]
+                },
+            ]
+        );
+        assert!(Poly::zero().support().is_empty());
+    }
+
+    #[test]
+    fn newton_polytope() {
+        let square = poly(&[
+            (1., vec![]),
+            (2., vec![("x", 1)]),
+            (3., vec![("y", 1)]),
+            (4., vec![("x", 1), ("y", 1)]),
+        ]);
+        assert_eq!(
+            square.newton_polytope(&["x", "y"]),
+            Some(vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]])
+        );
+
+        // xy is interior, x2y lies on the edge between x3 and y3
+        let triangle = poly(&[
+            (1., vec![("x", 3)]),
+            (1., vec![("y", 3)]),
+            (1., vec![]),
+            (5., vec![("x", 1), ("y", 1)]),
+            (-2., vec![("x", 2), ("y", 1)]),
+        ]);
+        assert_eq!(
+            triangle.newton_polytope(&["x", "y"]),
+            Some(vec![vec![0, 0], vec![0, 3], vec![3, 0]])
+        );
+
+        let line = poly(&[(1., vec![("x", 4)]), (1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(line.newton_polytope(&["x"]), Some(vec![vec![0], vec![4]]));
+
+        // yz is the midpoint of y2 and z2
+        let solid = poly(&[
+            (1., vec![]),
+            (1., vec![("x", 2)]),
+            (1., vec![("y", 2)]),
+            (1., vec![("z", 2)]),
+            (1., vec![("y", 1), ("z", 1)]),
+            (1., vec![("x", 1), ("y", 1), ("z", 1)]),
+        ]);
+        assert_eq!(
+            solid.newton_polytope(&["x", "y", "z"]),
+            Some(vec![
+                vec![0, 0, 0],
+                vec![0, 0, 2],
+                vec![0, 2, 0],
+                vec![1, 1, 1],
+                vec![2, 0, 0],
+            ])
+        );
+
+        assert_eq!(triangle.newton_polytope(&["x"]), None);
+        assert_eq!(Poly(vec![]).newton_polytope(&["x"]), Some(vec![]));
+    }
+
+    #[test]
+    fn to_aligned_string() {
+        let p1 = poly(&[(3., vec![("x", 2)]), (-1., vec![("x", 1)]), (1., vec![])]);
+        let p2 = poly(&[(1., vec![("y", 1)]), (-10., vec![])]);
+
+        assert_eq!(p1.to_aligned_string(6), "+3x2   -1x    +1");
+        assert_eq!(p2.to_aligned_string(6), "+y     -10");
+
+        // the second term starts in the same column on both lines
+        let col = |s: String| s.find('-');
+        assert_eq!(col(p1.to_aligned_string(6)), col(p2.to_aligned_string(6)));
+
+        // too narrow columns degrade to space separated terms
+        assert_eq!(p1.to_aligned_string(0), "+3x2 -1x +1");
+        assert_eq!(Poly(vec![]).to_aligned_string(4), "");
+    }
+
+    #[test]
+    fn substitute_is_canonical() {
+        let mut p = poly(&[
+            (1., vec![("x", 2)]),
+            (1., vec![("y", 1)]),
+            (3., vec![("x", 1), ("y", 2)]),
+            (-4., vec![]),
+        ]);
+        p.substitute("x", 2.);
+        assert_eq!(p, poly(&[(6., vec![("y", 2)]), (1., vec![("y", 1)])]));
+        assert_eq!(p.to_string(), "6y2 +y");
+
+        // substituting a symbol that isn't there still simplifies
+        let mut p = poly(&[(1., vec![("x", 1)]), (1., vec![("x", 1)])]);
+        p.substitute("z", 5.);
+        assert_eq!(p.to_string(), "2x");
+    }
+
+    #[test]
+    fn sqrt() {
+        let p = poly(&[(1., vec![("x", 2)]), (2., vec![("x", 1)]), (1., vec![])]);
+        assert_eq!(p.sqrt("x").unwrap().to_string(), "x +1");
+
+        let p = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        assert_eq!(p.sqrt("x"), None);
+
+        // (2x2 - 3)^2
+        let p = poly(&[(4., vec![("x", 4)]), (-12., vec![("x", 2)]), (9., vec![])]);
+        assert_eq!(p.sqrt("x").unwrap().to_string(), "2x2 -3");
+
+        assert_eq!(poly(&[(1., vec![("x", 3)])]).sqrt("x"), None);
+        assert_eq!(poly(&[(-1., vec![("x", 2)])]).sqrt("x"), None);
+        assert_eq!(poly(&[(4., vec![])]).sqrt("x").unwrap().to_string(), "2");
+        assert_eq!(Poly::zero().sqrt("x"), Some(Poly::zero()));
+        assert_eq!(poly(&[(1., vec![("x", 2), ("y", 2)])]).sqrt("x"), None);
+    }
+
+    #[test]
+    fn call() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (-3., vec![("x", 1)]),
+            (0.5, vec![("y", 3)]),
+            (7., vec![]),
+        ]);
+
+        let values = HashMap::from([("x".to_string(), 1.5), ("y".to_string(), -2.)]);
+        let constants = values
+            .iter()
+            .map(|(sym, val)| (sym.clone(), poly(&[(*val, vec![])])))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            p.call(&constants),
+            poly(&[(p.evaluate(&values).unwrap(), vec![])])
+        );
+
+        let bindings =
+            HashMap::from([("x".to_string(), poly(&[(1., vec![("t", 1)]), (1., vec![])]))]);
+        assert_eq!(
+            poly(&[(1., vec![("x", 2)]), (1., vec![("y", 1)])])
+                .call(&bindings)
+                .to_string(),
+            "t2 +2t +y +1"
+        );
+    }
+
+    #[test]
+    fn mul_simplify() {
+        let dense = |sym: &str, n: usize| {
+            Poly::from_coeffs(sym, &(0..=n).map(|i| i as f64 + 1.).collect::<Vec<_>>())
+        };
+        let (a, b) = (dense("x", 30), dense("x", 20));
+        assert_eq!(a.mul_simplify(&b), (&a * &b).canonical());
+        assert_eq!(a.mul_simplify(&b).len(), 51);
+
+        // (x + y)(x - y), with the xy terms cancelling and unsimplified inputs
+        let a = poly(&[(1., vec![("x", 1)]), (1., vec![("y", 1)])]);
+        let b = poly(&[
+            (1., vec![("x", 1)]),
+            (-2., vec![("y", 1)]),
+            (1., vec![("y", 1)]),
+        ]);
+        assert_eq!(a.mul_simplify(&b).to_string(), "x2 -1y2");
+
+        assert_eq!(a.mul_simplify(&Poly::zero()), Poly::zero());
+    }
+
+    #[test]
+    fn antidifference() {
+        let p = poly(&[(2., vec![("x", 1)]), (1., vec![])]);
+        assert_eq!(p.antidifference("x").unwrap().to_string(), "x2");
+
+        let p = poly(&[(6., vec![("x", 2)])]);
+        assert_eq!(p.antidifference("x").unwrap().to_string(), "2x3 -3x2 +x");
+
+        // finite_difference undoes it, other symbols ride along
+        let p = poly(&[
+            (3., vec![("x", 4), ("y", 1)]),
+            (-1., vec![("x", 1)]),
+            (0.5, vec![("y", 2)]),
+            (7., vec![]),
+        ]);
+        let f = p.antidifference("x").unwrap();
+        assert!(f.finite_difference("x").approx_eq(&p, 1e-9));
+        let mut f0 = f;
+        f0.substitute("x", 0.);
+        assert_eq!(f0, Poly::zero());
+
+        assert_eq!(Poly::zero().antidifference("x"), Some(Poly::zero()));
+        assert_eq!(poly(&[(1., vec![("x", 60)])]).antidifference("x"), None);
+    }
+
+    #[test]
+    fn to_csv() {
+        let p = poly(&[(1., vec![("x", 2)])]);
+        assert_eq!(
+            p.to_csv("x", 0., 1., 3).unwrap(),
+            "x,y\n0,0\n0.5,0.25\n1,1\n"
+        );
+
+        let p = poly(&[(2., vec![("t", 3)]), (-1., vec![("t", 1)]), (0.1, vec![])]);
+        let csv = p.to_csv("t", -2., 2., 7).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "t,y");
+        for line in &lines[1..] {
+            let fields = line
+                .split(',')
+                .map(|field| field.parse::<f64>().unwrap())
+                .collect::<Vec<_>>();
+            let [x, y] = fields[..] else {
+                panic!("expected two fields in {:?}", line)
+            };
+            assert!((y - (2. * x * x * x - x + 0.1)).abs() < 1e-12);
+        }
+
+        assert!(poly(&[(1., vec![("a,b", 1)])])
+            .to_csv("a,b", 0., 1., 1)
+            .unwrap()
+            .starts_with("\"a,b\",y\n"));
+        assert_eq!(p.to_csv("x", 0., 1., 3), None);
+    }
+
+    #[test]
+    fn factor_out_content() {
+        let p = poly(&[(6., vec![("x", 2)]), (9., vec![("x", 1)]), (3., vec![])]);
+        let (c, q) = p.factor_out_content();
+        assert_eq!((c, q.to_string()), (3., "2x2 +3x +1".to_string()));
+
+        let split = |p: Poly| {
+            let (c, q) = p.factor_out_content();
+            (c, q.to_string())
+        };
+        let neg = poly(&[(-4., vec![("x", 1)]), (-6., vec![])]);
+        assert_eq!(split(neg), (-2., "2x +3".to_string()));
+        let mixed = poly(&[(-4., vec![("x", 1)]), (6., vec![("y", 1)])]);
+        assert_eq!(split(mixed), (2., "-2x +3y".to_string()));
+        let fractional = poly(&[(1.5, vec![("x", 1)]), (3., vec![])]);
+        assert_eq!(split(fractional), (1., "1.5x +3".to_string()));
+        assert_eq!(split(poly(&[(-5., vec![])])), (-5., "1".to_string()));
+        assert_eq!(split(Poly::zero()), (1., "".to_string()));
+    }
+
+    #[test]
+    fn to_coeffs_array() {
+        let cubic = poly(&[(2., vec![("x", 3)]), (-1., vec![("x", 1)]), (5., vec![])]);
+        assert_eq!(cubic.to_coeffs_array::<4>("x"), Ok([5., -1., 0., 2.]));
+        assert_eq!(
+            cubic.to_coeffs_array::<6>("x"),
+            Ok([5., -1., 0., 2., 0., 0.])
+        );
+        assert_eq!(
+            cubic.to_coeffs_array::<3>("x"),
+            Err(SymbaError::TooManyTerms { terms: 4, max: 3 })
+        );
+
+        // unsimplified input, with a cubic and a y that cancel
+        let p = poly(&[
+            (1., vec![("x", 1), ("x", 1)]),
+            (1., vec![("x", 3)]),
+            (-1., vec![("x", 3)]),
+            (2., vec![("y", 1)]),
+            (-2., vec![("y", 1)]),
+            (1., vec![("x", 2)]),
+        ]);
+        assert_eq!(p.to_coeffs_array::<3>("x"), Ok([0., 0., 2.]));
+
+        assert_eq!(
+            poly(&[(1., vec![("x", 1), ("y", 1)])]).to_coeffs_array::<4>("x"),
+            Err(SymbaError::NotUnivariate {
+                sym: "x".to_string()
+            })
+        );
+        assert_eq!(Poly::zero().to_coeffs_array::<0>("x"), Ok([]));
+    }
+
+    #[test]
+    fn simplify_ordered() {
+        let mut p = poly(&[
+            (1., vec![("x", 1), ("y", 1), ("z", 1)]),
+            (1., vec![("x", 2)]),
+            (1., vec![("y", 1), ("z", 1), ("x", 1)]),
+        ]);
+        p.simplify_ordered(&["z", "y", "x"]);
+        assert_eq!(p.to_string(), "2zyx +x2");
+
+        let syms = |term: &PolyTerm| term.vars.iter().map(|v| v.sym.clone()).collect::<Vec<_>>();
+        assert_eq!(syms(&p.0[0]), ["z", "y", "x"]);
+
+        // unlisted symbols come after, alphabetically
+        let mut p = poly(&[(1., vec![("a", 1), ("b", 1), ("t", 1)])]);
+        p.simplify_ordered(&["t"]);
+        assert_eq!(p.to_string(), "tab");
+
+        p.simplify();
+        assert_eq!(p.to_string(), "abt");
+    }
+
+    #[test]
+    fn minimax() {
+        // the best line through x2 on [-1, 1] is the constant 1/2
+        let (p, err) = Poly::minimax("x", |x| x * x, -1., 1., 1, 10).unwrap();
+        assert!(p.approx_eq(&poly(&[(0.5, vec![])]), 1e-9));
+        assert!((err - 0.5).abs() < 1e-9);
+
+        let errors = (1..=6)
+            .map(|degree| Poly::minimax("x", f64::exp, -1., 1., degree, 20).unwrap().1)
+            .collect::<Vec<_>>();
+        assert!(errors.windows(2).all(|w| w[1] < w[0] / 5.));
+        // the known minimax error of a cubic for exp on [-1, 1]
+        assert!((errors[2] - 5.53e-3).abs() < 1e-5);
+
+        // off [-1, 1], and the reported error is the actual one
+        let (p, err) = Poly::minimax("t", f64::sin, 0., 3., 5, 20).unwrap();
+        let actual = p
+            .eval_grid("t", 0., 3., 301)
+            .unwrap()
+            .into_iter()
+            .map(|(t, y)| (y - t.sin()).abs())
+            .fold(0., f64::max);
+        assert!((actual - err).abs() < 1e-6 * err.max(1e-9) + 1e-12);
+        assert!(err < 1e-3);
+
+        assert_eq!(Poly::minimax("x", f64::exp, 1., 1., 2, 10), None);
+    }
+
+    #[test]
+    fn leading_coeff_in() {
+        let p = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (1., vec![("x", 1)]),
+            (1., vec![("y", 1)]),
+        ]);
+        assert_eq!(p.leading_coeff_in("x").to_string(), "y");
+        assert_eq!(p.leading_coeff_in("y").to_string(), "x2 +1");
+
+        // no z at all, so z^0 leads
+        assert_eq!(p.leading_coeff_in("z"), p.canonical());
+        assert_eq!(Poly::zero().leading_coeff_in("x"), Poly::zero());
+    }
+
+    #[test]
+    fn pseudo_div_rem() {
+        let check = |a: &Poly, b: &Poly, sym: &str| {
+            let (q, r) = a.pseudo_div_rem(b, sym);
+            let lc = b.leading_coeff_in(sym);
+            let k = (a.as_poly_in(sym)[0].0 + 1).saturating_sub(b.as_poly_in(sym)[0].0);
+            let mut lhs = &lc.pow(k) * a;
+            lhs.simplify();
+            let mut rhs = b * &q;
+            rhs += &r;
+            assert!(lhs.approx_eq(&rhs, 1e-9), "{} != {}", lhs, rhs);
+            assert!(r.0.is_empty() || r.as_poly_in(sym)[0].0 < b.as_poly_in(sym)[0].0);
+            (q.to_string(), r.to_string())
+        };
+
+        let a = poly(&[(1., vec![("x", 2)]), (1., vec![])]);
+        let b = poly(&[(2., vec![("x", 1)]), (1., vec![])]);
+        assert_eq!(check(&a, &b, "x"), ("2x -1".to_string(), "5".to_string()));
+
+        let a = poly(&[
+            (1., vec![("x", 2), ("y", 1)]),
+            (1., vec![("x", 1)]),
+            (1., vec![("y", 1)]),
+        ]);
+        let b = poly(&[(1., vec![("x", 1), ("y", 1)]), (1., vec![("y", 2)])]);
+        assert_eq!(
+            check(&a, &b, "x"),
+            ("xy2 -1y3 +y".to_string(), "y5".to_string())
+        );
+
+        let a = poly(&[
+            (3., vec![("x", 4), ("y", 1)]),
+            (-1., vec![("x", 2), ("z", 2)]),
+            (2., vec![("x", 1)]),
+            (5., vec![("y", 3)]),
+        ]);
+        let b = poly(&[
+            (2., vec![("x", 2), ("y", 1), ("z", 1)]),
+            (-1., vec![("x", 1)]),
+            (1., vec![("z", 1)]),
+        ]);
+        check(&a, &b, "x");
+        check(&a, &b, "y");
+
+        // a lower degree dividend is its own remainder, with k = 0
+        assert_eq!(b.pseudo_div_rem(&a, "x"), (Poly::zero(), b.canonical()));
+    }
+
+    #[test]
+    fn exact_arithmetic_overflow() {
+        // 0.1, 0.7 and 0.3 are read with denominators of 2^55 and more
+        let a = poly(&[(0.1, vec![("x", 3)]), (0.7, vec![("x", 1)]), (0.3, vec![])]);
+        let b = poly(&[(0.3, vec![("x", 1)]), (0.9, vec![])]);
+        assert_eq!(a.div_rem_exact(&b, "x"), None);
+        assert_eq!(a.gcd(&b, "x"), None);
+
+        let huge = poly(&[(1e30, vec![("x", 1)]), (0.1, vec![])]);
+        assert_eq!(huge.content(), Some(RatCoeff::new(1, 1 << 55)));
+        assert_eq!(huge.primitive_part(), None);
+        assert_eq!(huge.integrate_definite_exact("x", (0, 1), (3, 7)), None);
+
+        // whatever doesn't fit is left in the coeff
+        let p = poly(&[(0.5, vec![("x", 1)]), (1e30, vec![])]);
+        assert_eq!(
+            p.clear_denominators().0.to_string(),
+            "x +2000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn rational_roots_out_of_reach() {
+        // 10^30 is far too big to trial divide
+        let p = poly(&[(1., vec![("x", 3)]), (1e30, vec![])]);
+        assert_eq!(p.rational_root_candidates("x"), None);
+        assert_eq!(p.rational_roots("x"), None);
+        assert_eq!(p.factors_over_rationals("x"), None);
+
+        // within the bound
+        let p = poly(&[(1., vec![("x", 3)]), (-((1u64 << 39) as f64), vec![])]);
+        assert_eq!(
+            p.rational_roots("x"),
+            Some(vec![RatCoeff::from_integer(1 << 13)])
+        );
+
+        // 462 * 160 divisors, times two signs, are too many candidates
+        let (a, b) = (
+            2f64.powi(10) * 729. * 49. * 11.,
+            625. * 13. * 17. * 19. * 23. * 29.,
+        );
+        let p = poly(&[(a, vec![("x", 3)]), (b, vec![])]);
+        assert_eq!(p.rational_root_candidates("x"), None);
     }
 }
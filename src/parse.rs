@@ -0,0 +1,382 @@
+use std::str::FromStr;
+
+use crate::{Poly, PolyTerm, PolyVar, SymbaError};
+
+/// Parses what `Display` prints (`3x2 -1x10y +2(yy)3 +1`) as well as the
+/// usual hand written forms (`1/2 x^2 - 3/4`, `2*x*y^2`). The result is simplified.
+///
+/// - a bare letter is a single character symbol, longer ones go in parentheses: `(yy)`
+/// - exponents follow the symbol directly (`x2`) or after a caret (`x^2`)
+/// - a coeff, integer, decimal or `num/den`, may only start a term.
+///   `x/2` is division, which a polynomial term can't express, so it is an error
+impl FromStr for Poly {
+    type Err = SymbaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { s, pos: 0 }.poly()
+    }
+}
+
+impl Poly {
+    /// Parses exactly the layout `Display` prints and nothing else, so that
+    /// `Poly::parse_compact(&p.to_string()) == Ok(p)`. Unlike `from_str` the
+    /// terms are kept as written, not simplified, and the empty string is the
+    /// zero polynomial.
+    ///
+    /// 3x10 +2x2 => 3x^10 + 2x^2
+    ///
+    /// In this compact form an exponent directly follows its symbol, so only
+    /// single letter symbols are written bare: `x10` is always `x` to the
+    /// 10th, never a symbol `x1`. Every other symbol is in parentheses,
+    /// `(x1)10`. There is no `^`, `*`, `/` or extra whitespace, terms are
+    /// separated by a single space and carry a `+` unless negative, and a
+    /// coeff of 1 is left out on non constant terms.
+    ///
+    /// The round trip holds for every polynomial with finite coeffs, no
+    /// zero degree vars (which `Display` doesn't print) and no `)` in a
+    /// symbol. `simplify` takes care of the first two.
+    pub fn parse_compact(s: &str) -> Result<Poly, SymbaError> {
+        Parser { s, pos: 0 }.compact_poly()
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn poly(&mut self) -> Result<Poly, SymbaError> {
+        let mut terms = vec![];
+
+        self.skip_ws();
+        let mut sign = match self.eat('-') {
+            true => -1.,
+            false => {
+                self.eat('+');
+                1.
+            }
+        };
+        loop {
+            self.skip_ws();
+            let mut term = self.term()?;
+            term.coeff *= sign;
+            terms.push(term);
+
+            self.skip_ws();
+            let pos = self.pos;
+            sign = match self.bump() {
+                None => break,
+                Some('+') => 1.,
+                Some('-') => -1.,
+                Some(c) => return Err(error(pos, format!("unexpected '{}'", c))),
+            };
+        }
+
+        Ok(Poly::new(terms))
+    }
+
+    // 1/2 x^2 y
+    fn term(&mut self) -> Result<PolyTerm, SymbaError> {
+        let start = self.pos;
+        let coeff = match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => self.coeff()?,
+            _ => 1.,
+        };
+
+        let mut vars = vec![];
+        loop {
+            let before = self.pos;
+            self.skip_ws();
+            let star = self.eat('*');
+            self.skip_ws();
+
+            match self.peek() {
+                Some(c) if c.is_alphabetic() || c == '(' => vars.push(self.var()?),
+                Some('/') => {
+                    return Err(error(
+                        self.pos,
+                        "'/' is only allowed within a leading coeff like 1/2".into(),
+                    ))
+                }
+                _ if star => return Err(error(self.pos, "expected a symbol after '*'".into())),
+                _ => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+
+        match self.pos == start {
+            true => Err(error(start, "expected a term".into())),
+            false => Ok(PolyTerm { coeff, vars }),
+        }
+    }
+
+    // 3, 0.25, 1/2, 3 / 4
+    fn coeff(&mut self) -> Result<f64, SymbaError> {
+        let num = self.number()?;
+
+        let before = self.pos;
+        self.skip_ws();
+        if !self.eat('/') {
+            self.pos = before;
+            return Ok(num);
+        }
+        self.skip_ws();
+
+        match self.number()? {
+            0. => Err(SymbaError::DivisionByZero),
+            den => Ok(num / den),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, SymbaError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '.') {
+            self.bump();
+        }
+        self.s[start..self.pos]
+            .parse()
+            .map_err(|_| error(start, "expected a number".into()))
+    }
+
+    // x, x2, x^2, (yy)3, (yy)^3
+    fn var(&mut self) -> Result<PolyVar, SymbaError> {
+        let start = self.pos;
+        let sym = match self.bump() {
+            Some('(') => {
+                while matches!(self.peek(), Some(c) if c != ')') {
+                    self.bump();
+                }
+                let sym = self.s[start + 1..self.pos].trim();
+                if !self.eat(')') {
+                    return Err(error(start, "unclosed '('".into()));
+                }
+                if sym.is_empty() || !sym.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(error(start, format!("invalid symbol '{}'", sym)));
+                }
+                sym.to_string()
+            }
+            Some(c) => c.to_string(),
+            None => return Err(error(start, "expected a symbol".into())),
+        };
+
+        let before = self.pos;
+        self.skip_ws();
+        let deg = match self.eat('^') {
+            true => {
+                self.skip_ws();
+                self.degree()?
+            }
+            false => {
+                self.pos = before;
+                match self.peek() {
+                    Some(c) if c.is_ascii_digit() => self.degree()?,
+                    _ => 1,
+                }
+            }
+        };
+
+        Ok(PolyVar { sym, deg })
+    }
+
+    fn degree(&mut self) -> Result<usize, SymbaError> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.bump();
+        }
+        self.s[start..self.pos]
+            .parse()
+            .map_err(|_| error(start, "expected a non negative integer exponent".into()))
+    }
+
+    // 3x10 -1x10y +2(yy)3 +1
+    fn compact_poly(&mut self) -> Result<Poly, SymbaError> {
+        let mut terms = vec![];
+        if self.peek().is_none() {
+            return Ok(Poly(terms));
+        }
+
+        terms.push(self.compact_term()?);
+        while self.peek().is_some() {
+            if !self.eat(' ') {
+                return Err(error(self.pos, "expected a space between terms".into()));
+            }
+            // a negative term brings its own sign, without a '+'
+            match self.peek() {
+                Some('+') if !self.s[self.pos..].starts_with("+-") => self.bump(),
+                Some('-') => None,
+                _ => return Err(error(self.pos, "expected '+' or '-'".into())),
+            };
+            terms.push(self.compact_term()?);
+        }
+        Ok(Poly(terms))
+    }
+
+    // -1x2(yy), x2, 0.5
+    fn compact_term(&mut self) -> Result<PolyTerm, SymbaError> {
+        let start = self.pos;
+        let negative = self.eat('-');
+        let coeff = match self.peek() {
+            Some(c) if c.is_ascii_digit() => self.number()?,
+            _ if negative => return Err(error(self.pos, "expected a coeff after '-'".into())),
+            _ => 1.,
+        };
+
+        let mut vars = vec![];
+        while matches!(self.peek(), Some(c) if c.is_alphabetic() || c == '(') {
+            let sym = match self.bump() {
+                Some('(') => {
+                    let open = self.pos - 1;
+                    while matches!(self.peek(), Some(c) if c != ')') {
+                        self.bump();
+                    }
+                    let sym = &self.s[open + 1..self.pos];
+                    if !self.eat(')') {
+                        return Err(error(open, "unclosed '('".into()));
+                    }
+                    if sym.is_empty() {
+                        return Err(error(open, "empty symbol".into()));
+                    }
+                    sym.to_string()
+                }
+                Some(c) => c.to_string(),
+                None => unreachable!("peeked a symbol"),
+            };
+            let deg = match self.peek() {
+                Some(c) if c.is_ascii_digit() => self.degree()?,
+                _ => 1,
+            };
+            vars.push(PolyVar { sym, deg });
+        }
+
+        match self.pos == start {
+            true => Err(error(start, "expected a term".into())),
+            false => Ok(PolyTerm {
+                coeff: match negative {
+                    true => -coeff,
+                    false => coeff,
+                },
+                vars,
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.pos += c.len_utf8();
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+}
+
+fn error(pos: usize, reason: String) -> SymbaError {
+    SymbaError::Parse { pos, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rational_coeffs() {
+        let p: Poly = "1/2 x + 3/4".parse().unwrap();
+        assert_eq!(p.to_string(), "0.5x +0.75");
+
+        let p: Poly = "-1/2 x^2 y - 3 / 4".parse().unwrap();
+        assert_eq!(p.to_string(), "-0.5x2y -0.75");
+
+        let p: Poly = "2*x*(yy)^3 + 1/3x2".parse().unwrap();
+        assert_eq!(p.to_fraction_string(), "2x(yy)3 +1/3x2");
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let p: Poly = "3x2 -1x10y +2(yy)3 +1".parse().unwrap();
+        assert_eq!(p.to_string(), "-1x10y +2(yy)3 +3x2 +1");
+        assert_eq!(p.to_string().parse::<Poly>(), Ok(p));
+        assert_eq!("0".parse::<Poly>().unwrap(), Poly::zero());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            "x/2".parse::<Poly>(),
+            Err(SymbaError::Parse {
+                pos: 1,
+                reason: "'/' is only allowed within a leading coeff like 1/2".into()
+            })
+        );
+        assert_eq!("1/0 x".parse::<Poly>(), Err(SymbaError::DivisionByZero));
+        assert!("".parse::<Poly>().is_err());
+        assert!("x +".parse::<Poly>().is_err());
+        assert!("x 2".parse::<Poly>().is_err());
+        assert!("x^-1".parse::<Poly>().is_err());
+        assert!("2(x+1)".parse::<Poly>().is_err());
+        assert!("2*".parse::<Poly>().is_err());
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let p: Poly = "3x^10 + 2x^2".parse().unwrap();
+        assert_eq!(p.to_string(), "3x10 +2x2");
+        assert_eq!(Poly::parse_compact("3x10 +2x2"), Ok(p));
+
+        let p: Poly = "-x^10 y + 2 (x1)^3 (yy) - 0.1 + α^2 - 1/3 x"
+            .parse()
+            .unwrap();
+        assert_eq!(Poly::parse_compact(&p.to_string()), Ok(p.clone()));
+        assert_eq!(
+            p.to_string(),
+            "-1x10y +2(x1)3(yy) +α2 -0.3333333333333333x -0.1"
+        );
+
+        // x10 is x to the 10th, never the symbol x1
+        let p = Poly::parse_compact("x10").unwrap();
+        assert_eq!(
+            p.0[0].vars,
+            [PolyVar {
+                sym: "x".into(),
+                deg: 10
+            }]
+        );
+
+        // kept as written, not simplified
+        let p = Poly::parse_compact("x +x").unwrap();
+        assert_eq!(p.0.len(), 2);
+        assert_eq!(Poly::parse_compact(""), Ok(Poly::zero()));
+    }
+
+    #[test]
+    fn compact_errors() {
+        for s in [
+            "x^2", "2*x", "x + 1", "x  +1", "x +-1", "-x", "x1 +", "(yy", "()", "1/2x", " x",
+        ] {
+            assert!(
+                matches!(Poly::parse_compact(s), Err(SymbaError::Parse { .. })),
+                "{:?} parsed",
+                s
+            );
+        }
+    }
+}
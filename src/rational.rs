@@ -0,0 +1,404 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// An exact rational coefficient, always stored reduced with a positive denominator.
+///
+/// The operators panic on overflow of the underlying `i128`s, in release
+/// builds too, rather than wrap into a wrong value. Coeffs read from `f64`s
+/// that aren't short dyadic fractions (`0.1` has a denominator of `2^55`)
+/// overflow quickly, so code working on arbitrary input should use the
+/// `checked_*` methods, which give `None` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RatCoeff {
+    num: i128,
+    den: i128,
+}
+
+impl RatCoeff {
+    pub const ZERO: RatCoeff = RatCoeff { num: 0, den: 1 };
+    pub const ONE: RatCoeff = RatCoeff { num: 1, den: 1 };
+
+    /// Panics if `den` is zero.
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "RatCoeff with a zero denominator");
+        RatCoeff::checked_new(num, den).expect("RatCoeff overflow")
+    }
+
+    /// `new`, but `None` for a zero `den` or if `-i128::MIN` would be needed.
+    pub fn checked_new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+
+        let g = gcd(num, den);
+        let sign = den.signum();
+        Some(RatCoeff {
+            num: (num / g).checked_mul(sign)?,
+            den: (den / g).checked_mul(sign)?,
+        })
+    }
+
+    pub fn from_integer(n: i128) -> Self {
+        RatCoeff { num: n, den: 1 }
+    }
+
+    /// The exact value of a finite `f64` (every finite float is a dyadic rational).
+    /// `None` for non finite values or ones that don't fit in `i128`s.
+    ///
+    /// 0.75 => 3/4
+    pub fn from_f64(x: f64) -> Option<Self> {
+        if !x.is_finite() {
+            return None;
+        }
+        if x == 0. {
+            return Some(RatCoeff::ZERO);
+        }
+
+        let bits = x.to_bits();
+        let sign = match bits >> 63 {
+            0 => 1,
+            _ => -1,
+        };
+        let exp = ((bits >> 52) & 0x7ff) as i32;
+        let frac = (bits & ((1 << 52) - 1)) as i128;
+
+        // x = sign * mantissa * 2^exp
+        let (mut mantissa, mut exp) = match exp {
+            0 => (frac, -1074),
+            _ => (frac | (1 << 52), exp - 1075),
+        };
+        while mantissa % 2 == 0 && exp < 0 {
+            mantissa /= 2;
+            exp += 1;
+        }
+
+        match exp >= 0 {
+            true => {
+                let num = mantissa.checked_mul(1i128.checked_shl(exp as u32)?)?;
+                (num >> exp == mantissa).then(|| RatCoeff::from_integer(sign * num))
+            }
+            false => {
+                let den = 1i128.checked_shl((-exp) as u32).filter(|den| *den > 0)?;
+                Some(RatCoeff::new(sign * mantissa, den))
+            }
+        }
+    }
+
+    /// The simplest rational with denominator at most `max_den` that is
+    /// within floating point noise (a relative 1e-9) of `x`, found from the
+    /// continued fraction convergents of `x`. `None` if there is none.
+    ///
+    /// 0.3333333333333333 => 1/3
+    /// pi with max_den 1000 => None (355/113 is not close enough)
+    pub fn approximate(x: f64, max_den: i128) -> Option<Self> {
+        if !x.is_finite() || x.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        let close = |r: &RatCoeff| (r.to_f64() - x).abs() <= 1e-9 * x.abs().max(1.);
+
+        // convergents h/k with h(n) = a(n) h(n-1) + h(n-2), same for k
+        let (mut h, mut h_prev) = (1i128, 0i128);
+        let (mut k, mut k_prev) = (0i128, 1i128);
+        let mut rest = x;
+        loop {
+            let a = rest.floor();
+            (h, h_prev) = ((a as i128).checked_mul(h)?.checked_add(h_prev)?, h);
+            (k, k_prev) = ((a as i128).checked_mul(k)?.checked_add(k_prev)?, k);
+            if k > max_den {
+                return None;
+            }
+
+            let r = RatCoeff::checked_new(h, k)?;
+            if close(&r) {
+                return Some(r);
+            }
+
+            rest = 1. / (rest - a);
+            if !rest.is_finite() || rest.abs() >= i64::MAX as f64 {
+                return None;
+            }
+        }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let den = checked_lcm(self.den, rhs.den)?;
+        let a = self.num.checked_mul(den / self.den)?;
+        let b = rhs.num.checked_mul(den / rhs.den)?;
+        RatCoeff::checked_new(a.checked_add(b)?, den)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(RatCoeff {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // cross reduce first to keep the intermediate products small
+        let g1 = gcd(self.num, rhs.den);
+        let g2 = gcd(rhs.num, self.den);
+        RatCoeff::checked_new(
+            (self.num / g1).checked_mul(rhs.num / g2)?,
+            (self.den / g2).checked_mul(rhs.den / g1)?,
+        )
+    }
+
+    /// `None` on overflow and for a zero `rhs`.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(RatCoeff::checked_new(rhs.den, rhs.num)?)
+    }
+}
+
+/// gcd of the absolute values, `gcd(0, 0) == 1` so it is always safe to divide by.
+/// panics only for `gcd(i128::MIN, 0)` and `gcd(i128::MIN, i128::MIN)`, whose
+/// gcd of `2^127` doesn't fit.
+pub(crate) fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    i128::try_from(a.max(1)).expect("gcd overflow")
+}
+
+/// the (non negative) lcm, `None` if it doesn't fit in an `i128`.
+pub(crate) fn checked_lcm(a: i128, b: i128) -> Option<i128> {
+    (a / gcd(a, b)).checked_mul(b)?.checked_abs()
+}
+
+impl Add for RatCoeff {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).expect("RatCoeff overflow")
+    }
+}
+
+impl Neg for RatCoeff {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("RatCoeff overflow")
+    }
+}
+
+impl Sub for RatCoeff {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).expect("RatCoeff overflow")
+    }
+}
+
+impl Mul for RatCoeff {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).expect("RatCoeff overflow")
+    }
+}
+
+impl Div for RatCoeff {
+    type Output = Self;
+
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self::Output {
+        assert!(!rhs.is_zero(), "RatCoeff division by zero");
+        self.checked_div(rhs).expect("RatCoeff overflow")
+    }
+}
+
+impl PartialOrd for RatCoeff {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RatCoeff {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // compare a/b with c/d by their continued fractions, which can't
+        // overflow like cross multiplying can. the denominators stay positive
+        let (mut a, mut b, mut c, mut d) = (self.num, self.den, other.num, other.den);
+        loop {
+            let (q1, r1) = (a.div_euclid(b), a.rem_euclid(b));
+            let (q2, r2) = (c.div_euclid(d), c.rem_euclid(d));
+            match (q1.cmp(&q2), r1, r2) {
+                (Ordering::Equal, 0, 0) => return Ordering::Equal,
+                (Ordering::Equal, 0, _) => return Ordering::Less,
+                (Ordering::Equal, _, 0) => return Ordering::Greater,
+                // r1/b < r2/d exactly when d/r2 < b/r1
+                (Ordering::Equal, _, _) => (a, b, c, d) = (d, r2, b, r1),
+                (ord, _, _) => return ord,
+            }
+        }
+    }
+}
+
+impl Display for RatCoeff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            _ => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
+}
+
+/// dense exact division, coeffs indexed by power. `b` must be trimmed and non zero.
+/// `None` on overflow.
+pub(crate) fn div_rem(a: &[RatCoeff], b: &[RatCoeff]) -> Option<(Vec<RatCoeff>, Vec<RatCoeff>)> {
+    let lead = b[b.len() - 1];
+    if a.len() < b.len() {
+        return Some((vec![], a.to_vec()));
+    }
+
+    let mut r = a.to_vec();
+    let mut q = vec![RatCoeff::ZERO; a.len() - b.len() + 1];
+    for i in (0..q.len()).rev() {
+        let factor = r[i + b.len() - 1].checked_div(lead)?;
+        q[i] = factor;
+        for (j, coeff) in b.iter().enumerate() {
+            r[i + j] = r[i + j].checked_sub(factor.checked_mul(*coeff)?)?;
+        }
+    }
+
+    r.truncate(b.len() - 1);
+    trim(&mut r);
+    Some((q, r))
+}
+
+/// monic gcd by euclid's algorithm. the gcd of two zero polynomials is zero.
+/// `None` on overflow.
+pub(crate) fn poly_gcd(a: &[RatCoeff], b: &[RatCoeff]) -> Option<Vec<RatCoeff>> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    while !b.is_empty() {
+        let (_, r) = div_rem(&a, &b)?;
+        (a, b) = (b, r);
+    }
+
+    if let Some(&lead) = a.last() {
+        for coeff in &mut a {
+            *coeff = coeff.checked_div(lead)?;
+        }
+    }
+    Some(a)
+}
+
+pub(crate) fn trim(coeffs: &mut Vec<RatCoeff>) {
+    while coeffs.last().is_some_and(RatCoeff::is_zero) {
+        coeffs.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = RatCoeff::new(1, 3);
+        let b = RatCoeff::new(-2, 4);
+
+        assert_eq!(b, RatCoeff::new(1, -2));
+        assert_eq!(a + b, RatCoeff::new(-1, 6));
+        assert_eq!(a - b, RatCoeff::new(5, 6));
+        assert_eq!(a * b, RatCoeff::new(-1, 6));
+        assert_eq!(a / b, RatCoeff::new(-2, 3));
+        assert!(b < a);
+        assert_eq!((a + a + a).to_string(), "1");
+        assert_eq!(b.to_string(), "-1/2");
+    }
+
+    #[test]
+    fn from_f64() {
+        assert_eq!(RatCoeff::from_f64(0.75), Some(RatCoeff::new(3, 4)));
+        assert_eq!(RatCoeff::from_f64(-6.), Some(RatCoeff::from_integer(-6)));
+        assert_eq!(RatCoeff::from_f64(0.), Some(RatCoeff::ZERO));
+        assert_eq!(
+            RatCoeff::from_f64(2f64.powi(100)),
+            Some(RatCoeff::from_integer(1 << 100))
+        );
+        assert_eq!(RatCoeff::from_f64(1e300), None);
+        assert_eq!(RatCoeff::from_f64(f64::NAN), None);
+
+        let third = RatCoeff::from_f64(1. / 3.).unwrap();
+        assert_eq!(third.to_f64(), 1. / 3.);
+    }
+
+    #[test]
+    fn approximate() {
+        assert_eq!(
+            RatCoeff::approximate(1. / 3., 1000),
+            Some(RatCoeff::new(1, 3))
+        );
+        assert_eq!(
+            RatCoeff::approximate(-2.5, 1000),
+            Some(RatCoeff::new(-5, 2))
+        );
+        assert_eq!(
+            RatCoeff::approximate(7., 1),
+            Some(RatCoeff::from_integer(7))
+        );
+        assert_eq!(
+            RatCoeff::approximate(0.1 + 0.2, 1000),
+            Some(RatCoeff::new(3, 10))
+        );
+        assert_eq!(RatCoeff::approximate(2f64.sqrt(), 100), None);
+        assert_eq!(RatCoeff::approximate(std::f64::consts::PI, 1000), None);
+        assert_eq!(RatCoeff::approximate(f64::INFINITY, 1000), None);
+    }
+
+    #[test]
+    fn checked() {
+        let big = RatCoeff::from_integer(i128::MAX);
+        let half = RatCoeff::new(1, 2);
+        assert_eq!(big.checked_add(RatCoeff::ONE), None);
+        assert_eq!(big.checked_mul(big), None);
+        assert_eq!(big.checked_sub(half), None);
+        assert_eq!(half.checked_div(RatCoeff::ZERO), None);
+        assert_eq!(RatCoeff::checked_new(1, 0), None);
+        assert_eq!(RatCoeff::checked_new(i128::MIN, -1), None);
+        assert_eq!(half.checked_add(half), Some(RatCoeff::ONE));
+
+        // ordering doesn't need the cross products
+        let a = RatCoeff::new(i128::MAX - 1, i128::MAX);
+        let b = RatCoeff::new(i128::MAX - 2, i128::MAX - 1);
+        assert!(b < a);
+        assert!(-a < -b);
+        assert_eq!(a.cmp(&a), Ordering::Equal);
+        assert!(RatCoeff::new(7, 3) > RatCoeff::new(-7, 3));
+        assert!(RatCoeff::new(-7, 3) < RatCoeff::from_integer(-2));
+    }
+
+    #[test]
+    #[should_panic(expected = "RatCoeff overflow")]
+    fn operators_panic_on_overflow() {
+        let _ = RatCoeff::from_integer(i128::MAX) + RatCoeff::ONE;
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fmt::Display,
+    ops::{AddAssign, SubAssign},
+};
+
+use crate::{Poly, SymbaError};
+
+/// A `Poly` together with the tolerance used for its floating point
+/// comparisons, so a whole computation can share one numeric policy instead
+/// of passing `eps` to every call.
+///
+/// `simplify` drops the coeffs within `eps` of zero (`simplify_with_tolerance`),
+/// `==` and `approx_eq` compare coeff by coeff within `eps`, and
+/// `checked_div_rem` checks its reconstruction within `eps`. A tolerance of
+/// zero behaves exactly like the plain `Poly` methods.
+///
+/// `Poly` stays a plain tuple of its terms, so the tolerance lives here
+/// rather than in a field every `Poly(vec![..])` would have to spell out.
+#[derive(Debug, Clone)]
+pub struct Tolerant {
+    poly: Poly,
+    eps: f64,
+}
+
+impl Tolerant {
+    /// Panics if `eps` is negative or NaN.
+    pub fn new(poly: Poly, eps: f64) -> Self {
+        assert!(eps >= 0., "negative or NaN tolerance");
+        Tolerant { poly, eps }
+    }
+
+    pub fn eps(&self) -> f64 {
+        self.eps
+    }
+
+    /// Panics if `eps` is negative or NaN.
+    pub fn set_eps(&mut self, eps: f64) -> &mut Self {
+        assert!(eps >= 0., "negative or NaN tolerance");
+        self.eps = eps;
+        self
+    }
+
+    pub fn get(&self) -> &Poly {
+        &self.poly
+    }
+
+    pub fn get_mut(&mut self) -> &mut Poly {
+        &mut self.poly
+    }
+
+    pub fn into_inner(self) -> Poly {
+        self.poly
+    }
+
+    pub fn simplify(&mut self) -> &mut Self {
+        self.poly.simplify_with_tolerance(self.eps);
+        self
+    }
+
+    /// `Poly::approx_eq` with the stored tolerance.
+    pub fn approx_eq(&self, other: &Poly) -> bool {
+        self.poly.approx_eq(other, self.eps)
+    }
+
+    /// `Poly::checked_div_rem` with the stored tolerance. The quotient and
+    /// remainder keep it too.
+    pub fn checked_div_rem(
+        &self,
+        divisor: &Poly,
+        sym: &str,
+    ) -> Result<(Tolerant, Tolerant), SymbaError> {
+        let (q, r) = self.poly.checked_div_rem(divisor, sym, self.eps)?;
+        Ok((q.with_tolerance(self.eps), r.with_tolerance(self.eps)))
+    }
+}
+
+impl From<Poly> for Tolerant {
+    /// Exact, with a tolerance of zero.
+    fn from(poly: Poly) -> Self {
+        Tolerant::new(poly, 0.)
+    }
+}
+
+impl PartialEq for Tolerant {
+    /// Equal within the larger of the two tolerances, so `==` stays symmetric.
+    fn eq(&self, other: &Self) -> bool {
+        self.poly.approx_eq(&other.poly, self.eps.max(other.eps))
+    }
+}
+
+impl AddAssign<&Poly> for Tolerant {
+    fn add_assign(&mut self, rhs: &Poly) {
+        self.poly += rhs;
+    }
+}
+
+impl SubAssign<&Poly> for Tolerant {
+    fn sub_assign(&mut self, rhs: &Poly) {
+        self.poly -= rhs;
+    }
+}
+
+impl Display for Tolerant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.poly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PolyTerm, PolyVar};
+
+    fn x(coeff: f64, deg: usize) -> PolyTerm {
+        PolyTerm {
+            coeff,
+            vars: vec![PolyVar {
+                sym: "x".to_string(),
+                deg,
+            }],
+        }
+    }
+
+    #[test]
+    fn simplify_drops_near_zero_coeffs() {
+        // 0.1x + 0.2x - 0.3x leaves about 2.8e-17x behind
+        let p = Poly(vec![x(1., 2), x(0.1, 1), x(0.2, 1), x(-0.3, 1)]);
+
+        let mut exact = p.clone().with_tolerance(0.);
+        exact.simplify();
+        assert_eq!(exact.get(), &p.canonical());
+        assert_eq!(exact.get().0.len(), 2);
+
+        let mut loose = p.with_tolerance(1e-9);
+        loose.simplify();
+        assert_eq!(loose.get(), &Poly(vec![x(1., 2)]));
+        assert_eq!(loose.to_string(), "x2");
+    }
+
+    #[test]
+    fn comparisons_use_the_tolerance() {
+        let p = Poly(vec![x(1., 1), x(1., 0)]);
+        let q = Poly(vec![x(1. + 1e-12, 1), x(1., 0)]);
+
+        assert!(!Tolerant::from(p.clone()).approx_eq(&q));
+        assert!(p.clone().with_tolerance(1e-9).approx_eq(&q));
+        assert!(p.clone().with_tolerance(1e-9) == Tolerant::from(q.clone()));
+        assert!(Tolerant::from(q) == p.clone().with_tolerance(1e-9));
+
+        // (x2 - 1) / (x - 1)
+        let (quot, rem) = Poly(vec![x(1., 2), x(-1., 0)])
+            .with_tolerance(1e-9)
+            .checked_div_rem(&Poly(vec![x(1., 1), x(-1., 0)]), "x")
+            .unwrap();
+        assert_eq!(quot.get(), &p.canonical());
+        assert_eq!(rem.eps(), 1e-9);
+    }
+}